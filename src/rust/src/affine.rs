@@ -0,0 +1,277 @@
+use extendr_api::prelude::*;
+use sfconversions::{
+    vctrs::{as_rsgeo_vctr, rsgeo_type, verify_rsgeo},
+    Geom,
+};
+
+use geo::{AffineOps, AffineTransform, Centroid, MinimumRotatedRect, Rotate};
+use geo_types::{Geometry, Point};
+
+use crate::construction::IsReal;
+use crate::utils::new_data_frame;
+
+#[extendr]
+/// Rotate Geometries Around a Pivot
+///
+/// Rotates every geometry in `x` around a single shared pivot rather than
+/// each geometry's own centroid. This is useful for rotating an entire layout
+/// around a common anchor such as a city center.
+///
+/// @param x an object of class `rsgeo`
+/// @param degrees a numeric vector of length 1 or the same length as `x`.
+///   Positive angles rotate counter-clockwise and negative angles rotate clockwise.
+/// @param pivot an `rsgeo` vector of length 1. If `pivot` is not a point,
+///   its centroid is used as the origin of rotation.
+///
+/// @export
+/// @returns
+/// An object with the same class as `x`.
+/// @examples
+/// x <- geom_point(c(1, 2, 3), c(0, 0, 0))
+/// pivot <- geom_point(0, 0)
+/// rotate_around_geometry(x, 90, pivot)
+fn rotate_around_geometry(x: List, degrees: Doubles, pivot: List) -> Robj {
+    verify_rsgeo(&x);
+    verify_rsgeo(&pivot);
+
+    if pivot.len() != 1 {
+        panic!("`pivot` must be a geometry of length 1")
+    }
+
+    let n_x = x.len();
+    let n_d = degrees.len();
+
+    if (n_x != n_d) && (n_d != 1) {
+        panic!("`degrees` must be the same length as `x` or length 1")
+    }
+
+    let degrees = if n_d == 1 {
+        Doubles::from_values(vec![degrees[0]; n_x])
+    } else {
+        degrees
+    };
+
+    let pivot = pivot.elt(0).unwrap();
+
+    if pivot.is_null() {
+        panic!("`pivot` cannot be missing")
+    }
+
+    let origin = match <&Geom>::from_robj(&pivot).unwrap().geom.centroid() {
+        Some(cnt) => cnt,
+        None => panic!("`pivot` cannot be empty"),
+    };
+
+    // the output must be the same type as the input
+    let cls = rsgeo_type(&x);
+
+    let res_vec = x
+        .iter()
+        .zip(degrees.iter())
+        .map(|((_, xi), di)| {
+            if xi.is_null() || !di.is_real() {
+                NULL.into_robj()
+            } else {
+                let geo = <&Geom>::from_robj(&xi).unwrap();
+                let rotated = geo.geom.rotate_around_point(di.inner(), origin);
+                Geom::from(rotated).into_robj()
+            }
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res_vec), cls.as_str())
+}
+
+#[extendr]
+/// Translate Geometries by Points
+///
+/// Moves each geometry by an offset stored as the coordinates of a point.
+/// This is more convenient than separate x and y offsets when the offsets
+/// are themselves computed as vectors, such as a displacement field.
+///
+/// @param x an object of class `rsgeo`
+/// @param offsets an object of class `rs_POINT` of length 1 or the same
+///   length as `x`. The x and y coordinates of each point are the distances
+///   to move the corresponding geometry in the x and y directions.
+///
+/// @details
+///
+/// Geometries with a missing offset are returned unchanged.
+///
+/// @export
+/// @returns
+/// An object with the same class as `x`.
+/// @examples
+/// x <- geom_point(c(0, 1, 2), c(0, 0, 0))
+/// offsets <- geom_point(c(1, 0, -2), c(1, 5, 0))
+/// translate_by_points(x, offsets)
+fn translate_by_points(x: List, offsets: List) -> Robj {
+    verify_rsgeo(&x);
+
+    if !offsets.inherits("rs_POINT") {
+        panic!("`offsets` must be an `rs_POINT`")
+    }
+
+    let n_x = x.len();
+    let n_o = offsets.len();
+
+    if (n_x != n_o) && (n_o != 1) {
+        panic!("`offsets` must be the same length as `x` or length 1")
+    }
+
+    let offsets = offsets
+        .iter()
+        .map(|(_, oi)| {
+            if oi.is_null() {
+                None
+            } else {
+                let p: Point = Geom::try_from(oi).unwrap().geom.try_into().unwrap();
+                Some(p)
+            }
+        })
+        .collect::<Vec<Option<Point>>>();
+
+    let offsets = if n_o == 1 {
+        vec![offsets[0]; n_x]
+    } else {
+        offsets
+    };
+
+    // the output must be the same type as the input
+    let cls = rsgeo_type(&x);
+
+    let res_vec = x
+        .iter()
+        .zip(offsets)
+        .map(|((_, xi), oi)| {
+            if xi.is_null() {
+                return NULL.into_robj();
+            }
+
+            match oi {
+                Some(o) => {
+                    let geo = <&Geom>::from_robj(&xi).unwrap();
+                    let transform = AffineTransform::translate(o.x(), o.y());
+                    Geom::from(geo.geom.affine_transform(&transform)).into_robj()
+                }
+                None => xi,
+            }
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res_vec), cls.as_str())
+}
+
+// The angle in degrees of the long axis of the minimum rotated rectangle
+// of a geometry and the center of the rectangle.
+fn long_axis(x: &Geometry) -> Option<(f64, Point)> {
+    let rect = x.minimum_rotated_rect()?;
+    let crds = &rect.exterior().0;
+
+    if crds.len() < 3 {
+        return None;
+    }
+
+    let (e1, e2) = (crds[1] - crds[0], crds[2] - crds[1]);
+    let axis = if e1.x.hypot(e1.y) >= e2.x.hypot(e2.y) {
+        e1
+    } else {
+        e2
+    };
+
+    Some((axis.y.atan2(axis.x).to_degrees(), rect.centroid()?))
+}
+
+#[extendr]
+/// Orient Geometries to an Axis
+///
+/// Rotates each geometry so that the long axis of its minimum rotated
+/// rectangle points in the same direction. This normalizes the orientation
+/// of shapes such as building footprints or leaves before comparing them.
+///
+/// @param x an object of class `rsgeo`
+/// @param angle a numeric vector of length 1 or the same length as `x`. The
+///   direction of the long axis after rotation in degrees counter-clockwise
+///   from the x-axis. The default of `90` makes the long axis vertical.
+///
+/// @details
+///
+/// Each geometry is rotated around the center of its minimum rotated
+/// rectangle (see [`minimum_rotated_rect()`]) by the smallest angle that
+/// aligns the long axis with `angle`. The applied rotation is between -90
+/// and 90 degrees. Positive rotations are counter-clockwise.
+///
+/// @export
+/// @returns
+/// A `data.frame` with the same number of rows as `x` and columns:
+///
+/// - `geometry`: the rotated geometries with the same class as `x`.
+/// - `angle`: the rotation applied to each geometry in degrees.
+///
+/// Missing geometries and angles result in a missing geometry and angle.
+/// @examples
+/// x <- geom_polygon(c(0, 4, 5, 1, 0), c(0, 4, 3, -1, 0))
+/// orient_to_axis(x)
+/// orient_to_axis(x, 0)
+fn orient_to_axis(x: List, #[default = "90"] angle: Doubles) -> Robj {
+    verify_rsgeo(&x);
+
+    let n_x = x.len();
+    let n_a = angle.len();
+
+    if (n_x != n_a) && (n_a != 1) {
+        panic!("`angle` must be the same length as `x` or length 1")
+    }
+
+    let angle = if n_a == 1 {
+        Doubles::from_values(vec![angle[0]; n_x])
+    } else {
+        angle
+    };
+
+    // the output must be the same type as the input
+    let cls = rsgeo_type(&x);
+
+    let mut geoms: Vec<Robj> = Vec::with_capacity(n_x);
+    let mut applied: Vec<Option<f64>> = Vec::with_capacity(n_x);
+
+    for ((_, xi), ai) in x.iter().zip(angle.iter()) {
+        let axis = if xi.is_null() || !ai.is_real() {
+            None
+        } else {
+            long_axis(&<&Geom>::from_robj(&xi).unwrap().geom)
+        };
+
+        match axis {
+            Some((theta, center)) => {
+                // the axis is undirected so the rotation is in [-90, 90]
+                let mut rot = ai.inner() - theta;
+                rot -= 180.0 * (rot / 180.0).round();
+
+                let geo = <&Geom>::from_robj(&xi).unwrap();
+                geoms.push(Geom::from(geo.geom.rotate_around_point(rot, center)).into_robj());
+                applied.push(Some(rot));
+            }
+            None => {
+                geoms.push(NULL.into_robj());
+                applied.push(None);
+            }
+        }
+    }
+
+    new_data_frame(
+        ["geometry", "angle"],
+        [
+            as_rsgeo_vctr(List::from_values(geoms), cls.as_str()),
+            Doubles::from_values(applied).into_robj(),
+        ],
+        n_x,
+    )
+}
+
+extendr_module! {
+    mod affine;
+    fn rotate_around_geometry;
+    fn translate_by_points;
+    fn orient_to_axis;
+}
@@ -0,0 +1,180 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo::{BooleanOps, BoundingRect};
+use geo_types::{coord, Coord, Geometry, LineString, MultiPolygon, Polygon};
+
+use crate::triangulate::delaunay;
+use crate::union::coord_key;
+use crate::utils::new_data_frame;
+use rayon::prelude::*;
+use rstar::{primitives::GeomWithData, RTree};
+use std::collections::HashMap;
+
+fn points_from_list(x: List, arg: &str) -> Vec<Option<Coord>> {
+    if !x.inherits("rs_POINT") {
+        panic!("`{arg}` must be an `rs_POINT`")
+    }
+
+    geometry_from_list(x)
+        .into_iter()
+        .map(|xi| match xi? {
+            Geometry::Point(p) => Some(p.0),
+            _ => None,
+        })
+        .collect()
+}
+
+#[extendr]
+fn allocate_to_nearest_(demand: List, facilities: List) -> Robj {
+    let demand = points_from_list(demand, "demand");
+    let facilities = points_from_list(facilities, "facilities");
+
+    let tree = RTree::bulk_load(
+        facilities
+            .iter()
+            .enumerate()
+            .filter_map(|(i, fi)| fi.map(|c| GeomWithData::new([c.x, c.y], i)))
+            .collect::<Vec<GeomWithData<[f64; 2], usize>>>(),
+    );
+
+    let nearest = demand
+        .par_iter()
+        .map(|di| {
+            let di = (*di)?;
+            let mut cands = tree.nearest_neighbor_iter_with_distance_2(&[di.x, di.y]);
+            let (first, d2) = cands.next()?;
+
+            // ties go to the facility that comes first
+            let idx = cands
+                .take_while(|(_, d)| *d == d2)
+                .map(|(f, _)| f.data)
+                .fold(first.data, usize::min);
+
+            Some((idx, d2.sqrt()))
+        })
+        .collect::<Vec<Option<(usize, f64)>>>();
+
+    let n = demand.len();
+    let facility = nearest
+        .iter()
+        .map(|ni| ni.map(|(idx, _)| idx as i32 + 1))
+        .collect::<Vec<Option<i32>>>();
+    let distance = nearest
+        .iter()
+        .map(|ni| ni.map(|(_, d)| d))
+        .collect::<Vec<Option<f64>>>();
+
+    new_data_frame(
+        ["facility", "distance"],
+        [
+            Integers::from_values(facility).into_robj(),
+            Doubles::from_values(distance).into_robj(),
+        ],
+        n,
+    )
+}
+
+// Clips a convex ring to the half-plane of points that are at least as
+// close to `a` as they are to `b` (Sutherland-Hodgman).
+fn clip_half_plane(ring: &[Coord], a: Coord, b: Coord) -> Vec<Coord> {
+    let mid = (a + b) / 2.0;
+    let dir = b - a;
+    let side = |p: Coord| (p.x - mid.x) * dir.x + (p.y - mid.y) * dir.y;
+
+    let mut res: Vec<Coord> = Vec::with_capacity(ring.len() + 1);
+
+    for (i, &p) in ring.iter().enumerate() {
+        let q = ring[(i + 1) % ring.len()];
+        let (sp, sq) = (side(p), side(q));
+
+        if sp <= 0.0 {
+            res.push(p);
+        }
+
+        if (sp < 0.0 && sq > 0.0) || (sp > 0.0 && sq < 0.0) {
+            res.push(p + (q - p) * (sp / (sp - sq)));
+        }
+    }
+
+    res
+}
+
+#[extendr]
+fn voronoi_cells_(facilities: List, boundary: List) -> Robj {
+    if !(boundary.inherits("rs_POLYGON") || boundary.inherits("rs_MULTIPOLYGON")) {
+        panic!("`boundary` must be an `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    } else if boundary.len() != 1 {
+        panic!("`boundary` must be a geometry of length 1")
+    }
+
+    let boundary = match geometry_from_list(boundary).pop().flatten() {
+        Some(Geometry::Polygon(p)) => MultiPolygon::new(vec![p]),
+        Some(Geometry::MultiPolygon(mp)) => mp,
+        _ => panic!("`boundary` cannot be missing"),
+    };
+
+    let bbox = match boundary.bounding_rect() {
+        Some(bb) => bb,
+        None => panic!("`boundary` cannot be empty"),
+    };
+
+    let (min, max) = (bbox.min(), bbox.max());
+    let frame = vec![
+        min,
+        coord! { x: max.x, y: min.y },
+        max,
+        coord! { x: min.x, y: max.y },
+    ];
+
+    let coords = points_from_list(facilities.clone(), "facilities");
+    let tri = delaunay(facilities);
+
+    // the Voronoi cell of a point is bounded by its Delaunay neighbors
+    let mut neighbors = vec![Vec::new(); tri.coords.len()];
+    for &(a, b) in tri.edges.iter() {
+        neighbors[a].push(b);
+        neighbors[b].push(a);
+    }
+
+    let cells = tri
+        .coords
+        .par_iter()
+        .zip(neighbors.par_iter())
+        .map(|(c, nbs)| {
+            let ring = nbs.iter().fold(frame.clone(), |ring, &j| {
+                clip_half_plane(&ring, *c, tri.coords[j])
+            });
+
+            if ring.len() < 3 {
+                return MultiPolygon::new(vec![]);
+            }
+
+            let cell = Polygon::new(LineString::new(ring), vec![]);
+            MultiPolygon::new(vec![cell]).intersection(&boundary)
+        })
+        .collect::<Vec<MultiPolygon>>();
+
+    let index = tri
+        .coords
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (coord_key(c), i))
+        .collect::<HashMap<(u64, u64), usize>>();
+
+    let res = coords
+        .into_iter()
+        .map(|ci| match ci.and_then(|c| index.get(&coord_key(&c))) {
+            Some(&i) => Geom::from(cells[i].clone()).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "multipolygon")
+}
+
+extendr_module! {
+    mod allocate;
+    fn allocate_to_nearest_;
+    fn voronoi_cells_;
+}
@@ -0,0 +1,212 @@
+use extendr_api::prelude::*;
+use sfconversions::{
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, rsgeo_type, verify_rsgeo},
+    Geom,
+};
+
+use geo::{BooleanOps, BoundingRect, MapCoords};
+use geo_types::{coord, Coord, Geometry, LineString, MultiLineString, MultiPolygon, Polygon, Rect};
+
+use rayon::prelude::*;
+
+// normalizes a longitude into [-180, 180]
+fn wrap_lon(x: f64) -> f64 {
+    if (-180.0..=180.0).contains(&x) {
+        x
+    } else {
+        (x + 180.0).rem_euclid(360.0) - 180.0
+    }
+}
+
+fn wrap_coord(x: Coord) -> Coord {
+    coord! { x: wrap_lon(x.x), y: x.y }
+}
+
+// The index of the 360 degree strip (-180 + 360k, 180 + 360k] containing a
+// longitude. Strip 0 is the valid range of longitudes.
+fn lon_strip(x: f64) -> i64 {
+    ((x - 180.0) / 360.0).ceil() as i64
+}
+
+fn shift_coord(x: Coord, strip: i64) -> Coord {
+    coord! { x: x.x - 360.0 * strip as f64, y: x.y }
+}
+
+// Makes longitudes continuous by taking the shortest way around the globe
+// between consecutive coordinates. The result may extend beyond [-180, 180].
+fn unwrap_coords(x: &[Coord]) -> Vec<Coord> {
+    let mut res: Vec<Coord> = Vec::with_capacity(x.len());
+
+    for (i, c) in x.iter().enumerate() {
+        match res.last() {
+            None => res.push(*c),
+            Some(prev) => {
+                let mut delta = c.x - x[i - 1].x;
+                delta -= 360.0 * (delta / 360.0).round();
+                res.push(coord! { x: prev.x + delta, y: c.y });
+            }
+        }
+    }
+
+    res
+}
+
+// Splits a LineString into pieces wherever it crosses the antimeridian.
+// Each piece is shifted into [-180, 180].
+fn split_linestring(x: &LineString) -> Vec<LineString> {
+    let crds = unwrap_coords(&x.0);
+
+    let mut prev = match crds.first() {
+        Some(c) => *c,
+        None => return vec![],
+    };
+
+    let mut strip = lon_strip(prev.x);
+    let mut current = vec![shift_coord(prev, strip)];
+    let mut pieces: Vec<LineString> = Vec::new();
+
+    for &c in crds.iter().skip(1) {
+        let target = lon_strip(c.x);
+
+        while strip != target {
+            // the meridian that separates the current strip from the next
+            let (boundary, next) = if target > strip {
+                (180.0 + 360.0 * strip as f64, strip + 1)
+            } else {
+                (-180.0 + 360.0 * strip as f64, strip - 1)
+            };
+
+            let t = (boundary - prev.x) / (c.x - prev.x);
+            let crossing = coord! { x: boundary, y: prev.y + t * (c.y - prev.y) };
+
+            current.push(shift_coord(crossing, strip));
+            pieces.push(LineString::new(std::mem::take(&mut current)));
+            current.push(shift_coord(crossing, next));
+            strip = next;
+        }
+
+        current.push(shift_coord(c, strip));
+        prev = c;
+    }
+
+    pieces.push(LineString::new(current));
+    pieces.retain(|l| l.0.len() > 1 && l.0.windows(2).any(|w| w[0] != w[1]));
+    pieces
+}
+
+// Splits a Polygon into pieces wherever it crosses the antimeridian by
+// intersecting it with each 360 degree strip that it covers.
+fn split_polygon(x: &Polygon) -> Vec<Polygon> {
+    let exterior = unwrap_coords(&x.exterior().0);
+
+    let anchor = match exterior.first() {
+        Some(c) => c.x,
+        None => return vec![],
+    };
+
+    // a ring around a pole can't be split into strips
+    if exterior.first() != exterior.last() {
+        return vec![x.map_coords(wrap_coord)];
+    }
+
+    // holes are placed on the same side of the antimeridian as the exterior
+    let interiors = x
+        .interiors()
+        .iter()
+        .map(|r| {
+            let crds = unwrap_coords(&r.0);
+            let offset = match crds.first() {
+                Some(c) => 360.0 * ((anchor - c.x) / 360.0).round(),
+                None => 0.0,
+            };
+
+            LineString::from_iter(crds.into_iter().map(|c| coord! { x: c.x + offset, y: c.y }))
+        })
+        .collect::<Vec<LineString>>();
+
+    let unwrapped = Polygon::new(LineString::new(exterior), interiors);
+
+    let bbox = match unwrapped.bounding_rect() {
+        Some(b) => b,
+        None => return vec![],
+    };
+
+    let (min_strip, max_strip) = (lon_strip(bbox.min().x), lon_strip(bbox.max().x));
+
+    if min_strip == max_strip {
+        return vec![unwrapped.map_coords(|c| shift_coord(c, min_strip))];
+    }
+
+    (min_strip..=max_strip)
+        .flat_map(|strip| {
+            let west = -180.0 + 360.0 * strip as f64;
+            let mask = Rect::new(
+                coord! { x: west, y: bbox.min().y - 1.0 },
+                coord! { x: west + 360.0, y: bbox.max().y + 1.0 },
+            )
+            .to_polygon();
+
+            unwrapped
+                .intersection(&mask)
+                .0
+                .into_iter()
+                .map(move |p| p.map_coords(|c| shift_coord(c, strip)))
+        })
+        .collect()
+}
+
+fn split_at_antimeridian(x: Geometry) -> Geometry {
+    match x {
+        Geometry::LineString(l) => MultiLineString::new(split_linestring(&l)).into(),
+        Geometry::MultiLineString(ml) => {
+            MultiLineString::new(ml.iter().flat_map(split_linestring).collect()).into()
+        }
+        Geometry::Polygon(p) => MultiPolygon::new(split_polygon(&p)).into(),
+        Geometry::MultiPolygon(mp) => {
+            MultiPolygon::new(mp.iter().flat_map(split_polygon).collect()).into()
+        }
+        x => x.map_coords(wrap_coord),
+    }
+}
+
+#[extendr]
+fn wrap_longitude_(x: List, split: bool) -> Robj {
+    verify_rsgeo(&x);
+
+    let in_class = rsgeo_type(&x);
+
+    let out_class = match in_class.as_str() {
+        "linestring" if split => "multilinestring",
+        "polygon" if split => "multipolygon",
+        cls => cls,
+    };
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let xi = xi?;
+
+            if split {
+                Some(split_at_antimeridian(xi))
+            } else {
+                Some(xi.map_coords(wrap_coord))
+            }
+        })
+        .collect::<Vec<Option<Geometry>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), out_class)
+}
+
+extendr_module! {
+    mod antimeridian;
+    fn wrap_longitude_;
+}
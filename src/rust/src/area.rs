@@ -1,9 +1,23 @@
 use extendr_api::prelude::*;
 
 use geo::chamberlain_duquette_area::ChamberlainDuquetteArea;
+use geo::orient::{Direction, Orient};
 use geo::Area;
-use geo::GeodesicArea;
-use sfconversions::Geom;
+use geo::Centroid;
+use geo::{EuclideanLength, HaversineLength};
+use geo_types::{
+    Geometry, GeometryCollection, LineString, MultiLineString, MultiPolygon, Point, Polygon,
+};
+use sfconversions::{
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, rsgeo_type, verify_rsgeo},
+    Geom,
+};
+
+use crate::distance::check_metric;
+use crate::geodesic::{as_geodesic, geodesic_area_signed, geodesic_area_unsigned, geodesic_length};
+use geographiclib_rs::Geodesic;
+use rayon::prelude::*;
 
 #[extendr]
 /// Calculate the area of a polygon
@@ -11,6 +25,9 @@ use sfconversions::Geom;
 /// Functions to calculate different types of area for polygons.
 ///
 /// @param x an object of class `rsgeo`
+/// @param ellipsoid the name of an ellipsoid or a numeric vector of its
+///   semi-major axis in meters and flattening. One of `"WGS84"`, `"GRS80"`,
+///   `"sphere"`, `"moon"`, or `"mars"`. Defaults to `"WGS84"`.
 /// @export
 /// @rdname area
 /// @returns a numeric vector of the area contained by the geometry
@@ -20,6 +37,7 @@ use sfconversions::Geom;
 /// access standard
 /// - functions ending in `_cd` use the Chamberlain-Duquette algorithm for spherical area
 /// - Chamberlain-Duquette and Geodesic areas are returned in meters squared and assume non-planar geometries
+/// - geodesic areas are calculated on the ellipsoid given by `ellipsoid`. See [`geodesic_destination()`] for the named ellipsoids
 ///
 /// See geo docs for more:
 ///
@@ -38,6 +56,7 @@ use sfconversions::Geom;
 /// unsigned_area_cd(p)
 /// signed_area_geodesic(p)
 /// unsigned_area_geodesic(p)
+/// unsigned_area_geodesic(p, ellipsoid = "GRS80")
 fn signed_area(x: List) -> Doubles {
     x.iter()
         .map(|(_, xi)| {
@@ -108,17 +127,16 @@ fn unsigned_area_cd(x: List) -> Doubles {
 #[extendr]
 /// @export
 /// @rdname area
-fn unsigned_area_geodesic(x: List) -> Doubles {
+fn unsigned_area_geodesic(x: List, #[default = "'WGS84'"] ellipsoid: Robj) -> Doubles {
+    let g = as_geodesic(&ellipsoid);
+
     x.iter()
         .map(|(_, xi)| {
             if xi.is_null() {
                 Rfloat::na()
             } else {
-                let area = <&Geom>::from_robj(&xi)
-                    .unwrap()
-                    .geom
-                    .geodesic_area_unsigned();
-                Rfloat::from(area)
+                let geom = &<&Geom>::from_robj(&xi).unwrap().geom;
+                Rfloat::from(geodesic_area_unsigned(&g, geom))
             }
         })
         .collect::<Doubles>()
@@ -127,19 +145,416 @@ fn unsigned_area_geodesic(x: List) -> Doubles {
 #[extendr]
 /// @export
 /// @rdname area
-fn signed_area_geodesic(x: List) -> Doubles {
+fn signed_area_geodesic(x: List, #[default = "'WGS84'"] ellipsoid: Robj) -> Doubles {
+    let g = as_geodesic(&ellipsoid);
+
     x.iter()
         .map(|(_, xi)| {
             if xi.is_null() {
                 Rfloat::na()
             } else {
-                let area = <&Geom>::from_robj(&xi).unwrap().geom.geodesic_area_signed();
-                Rfloat::from(area)
+                let geom = &<&Geom>::from_robj(&xi).unwrap().geom;
+                Rfloat::from(geodesic_area_signed(&g, geom))
             }
         })
         .collect::<Doubles>()
 }
 
+// the perimeter of a polygon including its interior rings
+fn polygon_perimeter(x: &Polygon) -> f64 {
+    x.exterior().euclidean_length()
+        + x.interiors()
+            .iter()
+            .map(|r| r.euclidean_length())
+            .sum::<f64>()
+}
+
+fn is_sliver(x: &Polygon, min_area: f64, thinness: f64) -> bool {
+    let area = x.unsigned_area();
+    let perimeter = polygon_perimeter(x);
+
+    if area < min_area {
+        return true;
+    }
+
+    // degenerate polygons without a perimeter are always slivers
+    if perimeter == 0.0 {
+        return true;
+    }
+
+    (area / perimeter.powi(2)) < thinness
+}
+
+#[extendr]
+/// Remove Sliver Polygons
+///
+/// Removes small and thin polygons, commonly referred to as slivers, from
+/// polygonal geometries. Slivers are a common artifact of overlay operations.
+///
+/// @param x an object of class `rs_POLYGON` or `rs_MULTIPOLYGON`
+/// @param min_area polygons with an area smaller than `min_area` are removed.
+/// @param thinness polygons with a thinness ratio smaller than `thinness` are removed.
+///
+/// @details
+///
+/// The thinness ratio of a polygon is its area divided by the square of its
+/// perimeter. It is largest for a circle, approximately `0.0796`, whereas a
+/// square has a ratio of `0.0625`. Long and narrow polygons have a ratio
+/// close to `0`.
+///
+/// Each polygon in `x`, or each component of a multipolygon, is removed if
+/// either its area is smaller than `min_area` or its thinness ratio is smaller
+/// than `thinness`. Set either value to `0` to ignore it.
+///
+/// @export
+/// @returns
+/// An object of class `rs_MULTIPOLYGON`. Geometries where every polygon is
+/// removed are returned as missing.
+/// @examples
+/// ply <- geom_polygon(
+///   c(0, 10, 10, 0, 0, 12, 22, 22, 12, 12),
+///   c(0, 0, 10, 10, 0, 0, 0, 0.1, 0.1, 0),
+///   id = rep(1:2, each = 5)
+/// )
+/// mply <- combine_geoms(ply)
+/// remove_slivers(mply, 0, 0.01)
+fn remove_slivers(x: List, min_area: f64, thinness: f64) -> Robj {
+    if !(x.inherits("rs_POLYGON") || x.inherits("rs_MULTIPOLYGON")) {
+        panic!("`x` must be of class `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    if min_area.is_nan() || thinness.is_nan() {
+        panic!("`min_area` and `thinness` cannot be missing")
+    }
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let polys = match xi? {
+                Geometry::Polygon(p) => vec![p],
+                Geometry::MultiPolygon(mp) => mp.0,
+                _ => return None,
+            };
+
+            let kept = polys
+                .into_iter()
+                .filter(|p| !is_sliver(p, min_area, thinness))
+                .collect::<Vec<Polygon>>();
+
+            if kept.is_empty() {
+                None
+            } else {
+                Some(MultiPolygon::new(kept))
+            }
+        })
+        .collect::<Vec<Option<MultiPolygon>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "multipolygon")
+}
+
+// The polygon with the largest area. When multiple polygons share the
+// largest area, the first is returned.
+fn largest_part(x: Geometry) -> Option<Polygon> {
+    match x {
+        Geometry::Polygon(p) => Some(p),
+        Geometry::MultiPolygon(mp) => mp.0.into_iter().reduce(|largest, p| {
+            if p.unsigned_area() > largest.unsigned_area() {
+                p
+            } else {
+                largest
+            }
+        }),
+        _ => None,
+    }
+}
+
+#[extendr]
+/// Extract the Largest Polygon
+///
+/// Extracts the component polygon with the largest area from each
+/// multipolygon, discarding the rest. This is useful for keeping the mainland
+/// of a country and dropping small islands, e.g. before placing labels.
+///
+/// @param x an object of class `rs_POLYGON` or `rs_MULTIPOLYGON`
+///
+/// @details
+///
+/// Area is calculated using [`unsigned_area()`]. When multiple polygons share
+/// the largest area, the first is returned. Polygons are returned as is.
+///
+/// @export
+/// @returns
+/// An object of class `rs_POLYGON`. Empty multipolygons are returned as
+/// missing.
+/// @examples
+/// ply <- geom_polygon(
+///   c(0, 10, 10, 0, 0, 12, 14, 14, 12, 12),
+///   c(0, 0, 10, 10, 0, 0, 0, 2, 2, 0),
+///   id = rep(1:2, each = 5)
+/// )
+/// mply <- combine_geoms(ply)
+/// largest_polygon(mply)
+fn largest_polygon(x: List) -> Robj {
+    if !(x.inherits("rs_POLYGON") || x.inherits("rs_MULTIPOLYGON")) {
+        panic!("`x` must be of class `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| largest_part(xi?))
+        .collect::<Vec<Option<Polygon>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "polygon")
+}
+
+#[extendr]
+/// Centroid of the Largest Polygon
+///
+/// Finds the centroid of the component polygon with the largest area in each
+/// multipolygon. Unlike the centroid of the entire multipolygon, this point
+/// is placed on the main part of a feature, e.g. the mainland of a country
+/// rather than in the sea between its islands, which makes it useful for
+/// labeling.
+///
+/// @param x an object of class `rs_POLYGON` or `rs_MULTIPOLYGON`
+///
+/// @details
+///
+/// The largest polygon is found as in [`largest_polygon()`]. The centroid of a
+/// polygon is not guaranteed to lie inside of it.
+///
+/// @export
+/// @returns
+/// An object of class `rs_POINT`. Empty multipolygons are returned as missing.
+/// @examples
+/// ply <- geom_polygon(
+///   c(0, 10, 10, 0, 0, 12, 14, 14, 12, 12),
+///   c(0, 0, 10, 10, 0, 0, 0, 2, 2, 0),
+///   id = rep(1:2, each = 5)
+/// )
+/// mply <- combine_geoms(ply)
+/// centroid_largest(mply)
+fn centroid_largest(x: List) -> Robj {
+    if !(x.inherits("rs_POLYGON") || x.inherits("rs_MULTIPOLYGON")) {
+        panic!("`x` must be of class `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| largest_part(xi?)?.centroid())
+        .collect::<Vec<Option<Point>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "point")
+}
+
+#[extendr]
+fn compactness_(x: List, method: &str) -> Doubles {
+    verify_rsgeo(&x);
+
+    // a function of the area and perimeter of a shape
+    let score: fn(f64, f64) -> f64 = match method {
+        "polsby_popper" => |a: f64, p: f64| 4.0 * std::f64::consts::PI * a / p.powi(2),
+        "schwartzberg" => |a: f64, p: f64| 2.0 * (std::f64::consts::PI * a).sqrt() / p,
+        _ => panic!("`method` must be one of `polsby_popper` or `schwartzberg`"),
+    };
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let polys = match xi? {
+                Geometry::Polygon(p) => vec![p],
+                Geometry::MultiPolygon(mp) => mp.0,
+                Geometry::Rect(r) => vec![r.to_polygon()],
+                Geometry::Triangle(t) => vec![t.to_polygon()],
+                _ => return None,
+            };
+
+            let area = polys.iter().map(|p| p.unsigned_area()).sum::<f64>();
+            let perimeter = polys.iter().map(polygon_perimeter).sum::<f64>();
+
+            if perimeter == 0.0 {
+                None
+            } else {
+                Some(score(area, perimeter))
+            }
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res_vec)
+}
+
+// orients the polygons of a geometry following the right-hand rule
+fn orient_rhr(x: Geometry) -> Geometry {
+    match x {
+        Geometry::Polygon(p) => p.orient(Direction::Default).into(),
+        Geometry::MultiPolygon(mp) => mp.orient(Direction::Default).into(),
+        Geometry::GeometryCollection(gc) => gc
+            .into_iter()
+            .map(orient_rhr)
+            .collect::<GeometryCollection>()
+            .into(),
+        x => x,
+    }
+}
+
+#[extendr]
+/// Normalize Polygon Winding for GeoJSON
+///
+/// Orients polygon rings following the right-hand rule required by the
+/// GeoJSON specification (RFC 7946). Many GeoJSON consumers reject or
+/// misrender polygons with the wrong winding order.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @details
+///
+/// Exterior rings are made counter-clockwise and interior rings are made
+/// clockwise. Rings that already follow the right-hand rule are unchanged.
+/// Polygons inside of geometry collections are oriented as well. All other
+/// geometries are returned as is.
+///
+/// @export
+/// @returns an object of the same class as `x`
+/// @examples
+/// # a clockwise exterior ring
+/// ply <- geom_polygon(c(0, 0, 1, 1, 0), c(0, 1, 1, 0, 0))
+/// signed_area(ply)
+/// signed_area(normalize_geojson_winding(ply))
+fn normalize_geojson_winding(x: List) -> Robj {
+    verify_rsgeo(&x);
+    let cls = rsgeo_type(&x);
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| Some(orient_rhr(xi?)))
+        .collect::<Vec<Option<Geometry>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), cls.as_str())
+}
+
+// The length of linear geometries and the area of polygonal geometries.
+// Points have no extent and geometry collections are missing.
+fn measure_geom(x: Geometry, metric: &str, g: &Geodesic) -> Option<f64> {
+    let lines = match x {
+        Geometry::Point(_) | Geometry::MultiPoint(_) => return Some(0.0),
+        Geometry::Line(l) => MultiLineString::new(vec![LineString::from(l)]),
+        Geometry::LineString(l) => MultiLineString::new(vec![l]),
+        Geometry::MultiLineString(ml) => ml,
+        Geometry::Polygon(_)
+        | Geometry::MultiPolygon(_)
+        | Geometry::Rect(_)
+        | Geometry::Triangle(_) => {
+            let area = match metric {
+                "planar" => x.unsigned_area(),
+                "haversine" => x.chamberlain_duquette_unsigned_area(),
+                _ => geodesic_area_unsigned(g, &x),
+            };
+            return Some(area);
+        }
+        Geometry::GeometryCollection(_) => return None,
+    };
+
+    let length = match metric {
+        "planar" => lines.euclidean_length(),
+        "haversine" => lines.haversine_length(),
+        _ => lines.iter().map(|l| geodesic_length(g, l)).sum(),
+    };
+
+    Some(length)
+}
+
+#[extendr]
+fn measure_(x: List, metric: &str, ellipsoid: Robj) -> Doubles {
+    verify_rsgeo(&x);
+
+    check_metric(metric);
+    let g = as_geodesic(&ellipsoid);
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| measure_geom(xi?, metric, &g))
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res_vec)
+}
+
+fn perimeter_geom(x: Geometry, metric: &str, g: &Geodesic) -> Option<f64> {
+    let polygons = match x {
+        Geometry::Polygon(p) => vec![p],
+        Geometry::MultiPolygon(mp) => mp.0,
+        Geometry::Rect(r) => vec![r.to_polygon()],
+        Geometry::Triangle(t) => vec![t.to_polygon()],
+        _ => return None,
+    };
+
+    if metric == "planar" {
+        return Some(polygons.iter().map(polygon_perimeter).sum());
+    }
+
+    // exterior and interior rings
+    let rings = polygons
+        .into_iter()
+        .flat_map(|p| {
+            let (exterior, interiors) = p.into_inner();
+            std::iter::once(exterior).chain(interiors)
+        })
+        .collect::<MultiLineString>();
+
+    let length = match metric {
+        "haversine" => rings.haversine_length(),
+        _ => rings.iter().map(|r| geodesic_length(g, r)).sum(),
+    };
+
+    Some(length)
+}
+
+#[extendr]
+fn perimeter_(x: List, metric: &str, ellipsoid: Robj) -> Doubles {
+    verify_rsgeo(&x);
+
+    check_metric(metric);
+    let g = as_geodesic(&ellipsoid);
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| perimeter_geom(xi?, metric, &g))
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res_vec)
+}
+
 // Macro to generate exports
 extendr_module! {
     mod area;
@@ -149,4 +564,11 @@ extendr_module! {
     fn unsigned_area_cd;
     fn signed_area_geodesic;
     fn unsigned_area_geodesic;
+    fn remove_slivers;
+    fn largest_polygon;
+    fn centroid_largest;
+    fn compactness_;
+    fn normalize_geojson_winding;
+    fn measure_;
+    fn perimeter_;
 }
@@ -0,0 +1,268 @@
+use extendr_api::prelude::*;
+use sfconversions::{
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, verify_rsgeo},
+    Geom,
+};
+
+use geo::BoundingRect;
+use geo_types::{coord, Geometry, Rect};
+
+use rayon::prelude::*;
+
+// The bounding rectangle of each geometry. Geometries that are already
+// rectangles are used as is.
+fn rects_from_list(x: List) -> Vec<Option<Rect>> {
+    geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| match xi? {
+            Geometry::Rect(r) => Some(r),
+            g => g.bounding_rect(),
+        })
+        .collect()
+}
+
+// recycles a length 1 vector of rectangles to `n`
+fn recycle_rects(x: Vec<Option<Rect>>, n: usize, arg: &str) -> Vec<Option<Rect>> {
+    let n_arg = x.len();
+
+    if n_arg == 1 {
+        vec![x[0]; n]
+    } else if n_arg != n {
+        panic!("`{arg}` must be the same length as `x` or length 1")
+    } else {
+        x
+    }
+}
+
+fn as_bbox_vctr(x: Vec<Option<Rect>>) -> Robj {
+    let res = x
+        .into_iter()
+        .map(|xi| match xi {
+            Some(r) => Geom::from(Geometry::from(r)).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "bbox")
+}
+
+// the rectangle covered by both `x` and `y`
+fn rect_intersection(x: &Rect, y: &Rect) -> Option<Rect> {
+    let (xmin, ymin) = (x.min().x.max(y.min().x), x.min().y.max(y.min().y));
+    let (xmax, ymax) = (x.max().x.min(y.max().x), x.max().y.min(y.max().y));
+
+    if xmin > xmax || ymin > ymax {
+        None
+    } else {
+        Some(Rect::new(
+            coord! { x: xmin, y: ymin },
+            coord! { x: xmax, y: ymax },
+        ))
+    }
+}
+
+// the smallest rectangle covering both `x` and `y`
+fn rect_union(x: &Rect, y: &Rect) -> Rect {
+    Rect::new(
+        coord! { x: x.min().x.min(y.min().x), y: x.min().y.min(y.min().y) },
+        coord! { x: x.max().x.max(y.max().x), y: x.max().y.max(y.max().y) },
+    )
+}
+
+#[extendr]
+fn rs_bbox_(xmin: Doubles, ymin: Doubles, xmax: Doubles, ymax: Doubles) -> Robj {
+    let lens = [xmin.len(), ymin.len(), xmax.len(), ymax.len()];
+    let n = lens.into_iter().max().unwrap();
+
+    if lens.iter().any(|&l| l != n && l != 1) {
+        panic!("`xmin`, `ymin`, `xmax`, and `ymax` must be the same length or length 1")
+    }
+
+    let value = |x: &Doubles, i: usize| {
+        let xi = if x.len() == 1 { x[0] } else { x[i] };
+
+        if xi.is_na() || xi.is_nan() {
+            None
+        } else {
+            Some(xi.inner())
+        }
+    };
+
+    let res = (0..n)
+        .map(|i| {
+            let x0 = value(&xmin, i)?;
+            let y0 = value(&ymin, i)?;
+            let x1 = value(&xmax, i)?;
+            let y1 = value(&ymax, i)?;
+
+            if x0 > x1 || y0 > y1 {
+                panic!("`xmin` and `ymin` must be less than or equal to `xmax` and `ymax`")
+            }
+
+            Some(Rect::new(coord! { x: x0, y: y0 }, coord! { x: x1, y: y1 }))
+        })
+        .collect::<Vec<Option<Rect>>>();
+
+    as_bbox_vctr(res)
+}
+
+#[extendr]
+fn bbox_expand_(x: List, amount: Doubles) -> Robj {
+    verify_rsgeo(&x);
+
+    let n = x.len();
+    let n_a = amount.len();
+
+    if n_a != n && n_a != 1 {
+        panic!("`amount` must be the same length as `x` or length 1")
+    }
+
+    let amount = (0..n)
+        .map(|i| {
+            let ai = if n_a == 1 { amount[0] } else { amount[i] };
+
+            if ai.is_na() || ai.is_nan() {
+                None
+            } else {
+                Some(ai.inner())
+            }
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    let res = rects_from_list(x)
+        .into_iter()
+        .zip(amount)
+        .map(|(xi, ai)| {
+            let (r, a) = (xi?, ai?);
+            let (min, max) = (r.min(), r.max());
+
+            // boxes that are shrunk past a width or height of 0 are missing
+            if min.x - a > max.x + a || min.y - a > max.y + a {
+                return None;
+            }
+
+            Some(Rect::new(
+                coord! { x: min.x - a, y: min.y - a },
+                coord! { x: max.x + a, y: max.y + a },
+            ))
+        })
+        .collect::<Vec<Option<Rect>>>();
+
+    as_bbox_vctr(res)
+}
+
+#[extendr]
+/// @export
+/// @rdname bbox
+fn bbox_intersection(x: List, y: List) -> Robj {
+    verify_rsgeo(&x);
+    verify_rsgeo(&y);
+
+    let n = x.len();
+    let y = recycle_rects(rects_from_list(y), n, "y");
+
+    let res = rects_from_list(x)
+        .into_iter()
+        .zip(y)
+        .map(|(xi, yi)| rect_intersection(&xi?, &yi?))
+        .collect::<Vec<Option<Rect>>>();
+
+    as_bbox_vctr(res)
+}
+
+#[extendr]
+/// @export
+/// @rdname bbox
+fn bbox_union(x: List, y: List) -> Robj {
+    verify_rsgeo(&x);
+    verify_rsgeo(&y);
+
+    let n = x.len();
+    let y = recycle_rects(rects_from_list(y), n, "y");
+
+    let res = rects_from_list(x)
+        .into_iter()
+        .zip(y)
+        .map(|(xi, yi)| Some(rect_union(&xi?, &yi?)))
+        .collect::<Vec<Option<Rect>>>();
+
+    as_bbox_vctr(res)
+}
+
+#[extendr]
+/// @export
+/// @rdname bbox
+fn bbox_to_polygon(x: List) -> Robj {
+    verify_rsgeo(&x);
+
+    let res = rects_from_list(x)
+        .into_iter()
+        .map(|xi| match xi {
+            Some(r) => Geom::from(r.to_polygon()).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "polygon")
+}
+
+#[extendr]
+/// Bounding Box Metrics
+///
+/// Calculates the area and aspect ratio of the bounding box of each geometry
+/// without creating polygons. These are cheap proxies for filtering
+/// geometries, e.g. dropping long thin shapes, before more expensive
+/// operations.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @details
+///
+/// The aspect ratio is the width of the bounding box divided by its height.
+/// Bounding boxes with a height of 0 have an infinite aspect ratio and those
+/// with a width and height of 0, such as points, have an aspect ratio of `NaN`.
+///
+/// @export
+/// @rdname bbox_area
+/// @returns A numeric vector of the same length as `x`. Missing geometries
+/// are `NA`.
+/// @examples
+/// x <- geom_linestring(c(0, 10, 0, 1), c(0, 2, 0, 5), id = c(1, 1, 2, 2))
+/// bbox_area(x)
+/// bbox_aspect_ratio(x)
+fn bbox_area(x: List) -> Doubles {
+    verify_rsgeo(&x);
+
+    let res = rects_from_list(x)
+        .into_iter()
+        .map(|xi| xi.map(|r| r.width() * r.height()))
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res)
+}
+
+#[extendr]
+/// @export
+/// @rdname bbox_area
+fn bbox_aspect_ratio(x: List) -> Doubles {
+    verify_rsgeo(&x);
+
+    let res = rects_from_list(x)
+        .into_iter()
+        .map(|xi| xi.map(|r| r.width() / r.height()))
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res)
+}
+
+extendr_module! {
+    mod bbox;
+    fn rs_bbox_;
+    fn bbox_expand_;
+    fn bbox_intersection;
+    fn bbox_union;
+    fn bbox_to_polygon;
+    fn bbox_area;
+    fn bbox_aspect_ratio;
+}
@@ -7,8 +7,9 @@ use sfconversions::{
 use extendr_api::prelude::*;
 
 use crate::construction::IsReal;
+use crate::spatial_index::envelope_pairs;
 use geo::{BoundingRect, ConcaveHull, ConvexHull, Extremes, MinimumRotatedRect};
-use geo_types::{Geometry, Point, Polygon};
+use geo_types::{coord, Geometry, Point, Polygon, Rect};
 
 use rayon::prelude::*;
 
@@ -22,6 +23,8 @@ use rayon::prelude::*;
 ///
 /// @param x an object of class `rsgeo`
 /// @param concavity a value between 0 and 1 specifying the concavity of the convex hull
+/// @param as_bbox default `FALSE`. If `TRUE`, `bounding_box()` returns an `rs_BBOX`
+///   instead of a numeric vector. See [`rs_bbox()`].
 ///
 /// @export
 /// @rdname boundaries
@@ -41,7 +44,8 @@ use rayon::prelude::*;
 ///
 /// @returns
 ///
-/// - `bounding_box()` returns a named vector of xmin, ymin, xmax, and ymax
+/// - `bounding_box()` returns a named vector of xmin, ymin, xmax, and ymax or an
+///   `rs_BBOX` of length 1 when `as_bbox = TRUE`
 /// - `bounding_boxes()` returns a list of bounding box numeric vectors for each geometry
 /// - `bounding_rect()` returns an `rs_POLYGON` of the bounding rectangle of each geometry
 /// - `convex_hull()` returns an `rs_POLYGON` of the convex hull for each geometry
@@ -50,7 +54,7 @@ use rayon::prelude::*;
 ///  is a named vector of xmin, ymin, xmax, and ymax where each element is a `Point` geometry of the extreme value
 /// - `minimum_rotated_rect()` returns the minimum rotated rectangle covering a geometry as an `rs_POLYGON`
 
-fn bounding_box(x: List) -> Robj {
+fn bounding_box(x: List, #[default = "FALSE"] as_bbox: bool) -> Robj {
     let bbox = x
         .iter()
         .fold([f64::MAX, f64::MAX, f64::MIN, f64::MIN], |acc, (_, xi)| {
@@ -58,8 +62,14 @@ fn bounding_box(x: List) -> Robj {
 
             match g {
                 Ok(geo) => {
-                    let (xmin, ymin) = geo.geom.bounding_rect().unwrap().min().x_y();
-                    let (xmax, ymax) = geo.geom.bounding_rect().unwrap().max().x_y();
+                    // empty geometries have no bounding rectangle
+                    let bb = match geo.geom.bounding_rect() {
+                        Some(bb) => bb,
+                        None => return acc,
+                    };
+
+                    let (xmin, ymin) = bb.min().x_y();
+                    let (xmax, ymax) = bb.max().x_y();
 
                     [
                         acc[0].min(xmin),
@@ -74,6 +84,20 @@ fn bounding_box(x: List) -> Robj {
 
     // TODO what if all values are NA? We will be returning massive numbers and that wouldnt be good
 
+    if as_bbox {
+        let res = if bbox[0] > bbox[2] {
+            NULL.into_robj()
+        } else {
+            let rect = Rect::new(
+                coord! { x: bbox[0], y: bbox[1] },
+                coord! { x: bbox[2], y: bbox[3] },
+            );
+            Geom::from(Geometry::from(rect)).into_robj()
+        };
+
+        return as_rsgeo_vctr(List::from_values([res]), "bbox");
+    }
+
     Doubles::from_values(bbox)
         .into_robj()
         .set_names(["xmin", "ymin", "xmax", "ymax"])
@@ -87,7 +111,7 @@ fn bounding_boxes(x: List) -> List {
     let res_vec = x
         .iter()
         .map(|(_, xi)| {
-            if x.is_null() {
+            if xi.is_null() {
                 let bb = [Rfloat::na(); 4];
                 Doubles::from_values(bb)
                     .into_robj()
@@ -127,7 +151,7 @@ fn bounding_rect(x: List) -> Robj {
     let res_vec = x
         .iter()
         .map(|(_, xi)| {
-            if x.is_null() {
+            if xi.is_null() {
                 ().into_robj()
             } else {
                 let bb = <&Geom>::from_robj(&xi).unwrap().geom.bounding_rect();
@@ -287,6 +311,102 @@ fn minimum_rotated_rect(x: List) -> Robj {
     as_rsgeo_vctr(List::from_values(res), "polygon")
 }
 
+#[extendr]
+/// Filter Geometries by Bounding Box
+///
+/// Identifies the geometries whose bounding box intersects a query rectangle.
+/// Only bounding boxes are compared, making this a fast first pass before
+/// a more expensive spatial predicate.
+///
+/// @param x an object of class `rsgeo`
+/// @param xmin,ymin,xmax,ymax a scalar numeric defining the query rectangle
+///
+/// @export
+/// @returns
+/// An integer vector of the 1-based indices of the geometries in `x` whose
+/// bounding box intersects the query rectangle.
+/// @examples
+/// pnts <- geom_point(1:10, 1:10)
+/// idx <- bbox_filter(pnts, 2, 2, 5, 5)
+/// pnts[idx]
+fn bbox_filter(x: List, xmin: f64, ymin: f64, xmax: f64, ymax: f64) -> Integers {
+    verify_rsgeo(&x);
+
+    if [xmin, ymin, xmax, ymax].iter().any(|v| v.is_nan()) {
+        panic!("`xmin`, `ymin`, `xmax`, and `ymax` cannot be missing")
+    }
+
+    if xmin > xmax || ymin > ymax {
+        panic!("`xmin` and `ymin` must be less than or equal to `xmax` and `ymax`")
+    }
+
+    let idx = geometry_from_list(x)
+        .into_par_iter()
+        .enumerate()
+        .filter_map(|(i, xi)| {
+            let rect = xi?.bounding_rect()?;
+            let (x0, y0) = rect.min().x_y();
+            let (x1, y1) = rect.max().x_y();
+
+            if x0 <= xmax && x1 >= xmin && y0 <= ymax && y1 >= ymin {
+                Some(i as i32 + 1)
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<i32>>();
+
+    Integers::from_values(idx)
+}
+
+#[extendr]
+/// Find Overlapping Bounding Boxes
+///
+/// Identifies every pair of geometries in a vector whose bounding boxes
+/// intersect. This is the efficient first stage of a self-join such as
+/// finding intersecting geometries or clustering: the exact, and more
+/// expensive, predicate only needs to be checked for these candidate pairs.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @details
+///
+/// Candidates are found using an R*-tree so that all pairs of bounding boxes
+/// are not compared. Each pair is reported once with the smaller index first.
+/// A geometry is not paired with itself. Missing geometries are never paired.
+///
+/// @export
+/// @returns
+/// A two-column integer matrix with columns `i` and `j` of the 1-based indices
+/// of each overlapping pair where `i < j`. Rows are sorted by `i` then `j`.
+/// @examples
+/// lns <- geom_linestring(
+///   c(0, 2, 1, 3, 5, 6),
+///   c(0, 2, 1, 3, 5, 6),
+///   rep(1:3, each = 2)
+/// )
+/// bbox_overlaps_matrix(lns)
+fn bbox_overlaps_matrix(x: List) -> Robj {
+    verify_rsgeo(&x);
+
+    let pairs = envelope_pairs(x);
+    let n = pairs.len();
+
+    // the matrix is filled column by column
+    let values = pairs
+        .iter()
+        .map(|(i, _)| *i as i32 + 1)
+        .chain(pairs.iter().map(|(_, j)| *j as i32 + 1))
+        .collect::<Vec<i32>>();
+
+    Integers::from_values(values)
+        .into_robj()
+        .set_attrib("dim", [n as i32, 2])
+        .unwrap()
+        .set_attrib("dimnames", list!(NULL, Strings::from_values(["i", "j"])))
+        .unwrap()
+}
+
 extendr_module! {
     mod boundary;
     fn bounding_boxes;
@@ -296,4 +416,6 @@ extendr_module! {
     fn concave_hull;
     fn extreme_coords;
     fn bounding_box;
+    fn bbox_filter;
+    fn bbox_overlaps_matrix;
 }
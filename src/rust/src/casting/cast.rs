@@ -141,7 +141,9 @@ fn cast_linestring_multipoint(x: Geom) -> Geom {
 
 fn cast_linestring_polygon(x: Geom) -> Geom {
     let mut coords = LineString::from(x).0;
-    coords.push(coords[0]);
+    if let Some(&first) = coords.first() {
+        coords.push(first);
+    }
     Geom::from(Polygon::new(LineString::from(coords), vec![]))
 }
 
@@ -166,7 +168,9 @@ fn cast_multilinestring_multipolygon(x: Geom) -> Geom {
         x.0.into_iter()
             .map(|lns| {
                 let mut coords = lns.0;
-                coords.push(coords[0]);
+                if let Some(&first) = coords.first() {
+                    coords.push(first);
+                }
                 Polygon::new(LineString::from(coords), vec![])
             })
             .collect::<Vec<Polygon>>();
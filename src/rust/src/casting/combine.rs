@@ -4,6 +4,7 @@ use geo_types::*;
 // use sfconversions::vctrs::geom_class;
 //use crate::utils::geom_class;
 use sfconversions::{
+    geometry_from_list,
     vctrs::{as_rsgeo_vctr, verify_rsgeo},
     Geom,
 };
@@ -50,6 +51,55 @@ fn combine_linestrings(x: List) -> Robj {
     as_rsgeo_vctr(list!(res), "linestring")
 }
 
+#[extendr]
+/// Join LineStrings End to End
+///
+/// Concatenates the coordinates of LineStrings in the order they appear into
+/// a single LineString. Unlike [`union_geoms()`] the LineStrings do not need
+/// to touch; the end of each LineString is connected to the start of the next
+/// by a straight segment. This is useful when the intended order of the
+/// pieces of a line is already known.
+///
+/// @param x an object of class `rs_LINESTRING`
+/// @param drop_duplicates default `TRUE`. If `TRUE`, the first coordinate of
+///   a LineString is dropped when it is the same as the last coordinate of the
+///   previous one.
+///
+/// @details
+///
+/// Missing geometries are skipped. LineStrings are not reversed so the
+/// direction of each piece is kept.
+///
+/// @export
+/// @returns
+/// An object of class `rs_LINESTRING` of length one. It is missing when `x`
+/// has no coordinates.
+/// @examples
+/// x <- geom_linestring(c(0, 1, 1, 2, 3, 4), c(0, 0, 0, 1, 1, 1), rep(1:3, each = 2))
+/// join_linestrings(x)
+/// join_linestrings(x, drop_duplicates = FALSE)
+fn join_linestrings(x: List, #[default = "TRUE"] drop_duplicates: bool) -> Robj {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let mut crds: Vec<Coord> = Vec::new();
+
+    for xi in geometry_from_list(x).into_iter().flatten() {
+        let l = LineString::try_from(xi).unwrap();
+        let skip = drop_duplicates && crds.last().is_some() && crds.last() == l.0.first();
+        crds.extend(l.0.into_iter().skip(skip as usize));
+    }
+
+    let res = if crds.is_empty() {
+        NULL.into_robj()
+    } else {
+        Geom::from(LineString::new(crds)).into_robj()
+    };
+
+    as_rsgeo_vctr(list!(res), "linestring")
+}
+
 #[extendr]
 fn combine_multilinestrings(x: List) -> Robj {
     verify_rsgeo(&x);
@@ -92,6 +142,7 @@ extendr_module! {
     fn combine_multipoints;
     fn combine_linestrings;
     fn combine_multilinestrings;
+    fn join_linestrings;
     fn combine_polygons;
     fn combine_multipolygons;
 }
@@ -1,6 +1,10 @@
 use extendr_api::prelude::*;
 use geo_types::*;
-use sfconversions::{vctrs::as_rsgeo_vctr, Geom};
+use sfconversions::{
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, rsgeo_type, verify_rsgeo},
+    Geom,
+};
 
 // EXPAND -------------------------------------------------------------------------
 // multis to the single varietys
@@ -120,6 +124,67 @@ fn expand_geoms(x: List) -> List {
     List::from_values(res)
 }
 
+// the nth component of a geometry where `n` is 0-based. Single
+// geometries are their own first and only component.
+fn nth_geometry(x: Geometry, n: usize) -> Option<Geometry> {
+    match x {
+        Geometry::MultiPoint(g) => g.0.into_iter().nth(n).map(Geometry::from),
+        Geometry::MultiLineString(g) => g.0.into_iter().nth(n).map(Geometry::from),
+        Geometry::MultiPolygon(g) => g.0.into_iter().nth(n).map(Geometry::from),
+        Geometry::GeometryCollection(g) => g.0.into_iter().nth(n),
+        g => (n == 0).then_some(g),
+    }
+}
+
+#[extendr]
+fn geometry_n_(x: List, n: Integers) -> Robj {
+    verify_rsgeo(&x);
+
+    let n_x = x.len();
+    let n_n = n.len();
+
+    if (n_n != n_x) && (n_n != 1) {
+        panic!("`n` must be the same length as `x` or length 1")
+    }
+
+    let n = n
+        .iter()
+        .cycle()
+        .take(n_x)
+        .map(|ni| {
+            if ni.is_na() || ni.inner() < 1 {
+                None
+            } else {
+                Some(ni.inner() as usize - 1)
+            }
+        })
+        .collect::<Vec<Option<usize>>>();
+
+    let cls = match rsgeo_type(&x).as_str() {
+        "multipoint" => "point",
+        "multilinestring" => "linestring",
+        "multipolygon" => "polygon",
+        "geometrycollection" => "geometry",
+        other => other,
+    }
+    .to_string();
+
+    let res = geometry_from_list(x)
+        .into_iter()
+        .zip(n)
+        .map(|(xi, ni)| match (xi, ni) {
+            (Some(g), Some(i)) => nth_geometry(g, i),
+            _ => None,
+        })
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), cls.as_str())
+}
+
 // Expansion Hierarchy
 // MultiPolygon -> Polygon
 // (Polygon -> LineString) OR (MultiLineString -> LineString)
@@ -133,4 +198,5 @@ extendr_module! {
     fn expand_multipoint;
     fn expand_polygon;
     fn expand_geoms;
+    fn geometry_n_;
 }
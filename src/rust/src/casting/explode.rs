@@ -1,7 +1,8 @@
 use extendr_api::prelude::*;
 use geo::LinesIter;
 use geo_types::*;
-use sfconversions::{vctrs::as_rsgeo_vctr, Geom};
+use rayon::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
 
 #[extendr]
 fn explode_linestrings_(x: List) -> Robj {
@@ -46,8 +47,38 @@ fn explode_multilinestrings_(x: List) -> Robj {
     as_rsgeo_vctr(List::from_values(res_vec), "linestring")
 }
 
+// one MultiLineString of segments for each LineString or MultiLineString
+#[extendr]
+fn explode_lines_grouped_(x: List) -> Robj {
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let lines = match xi? {
+                Geometry::LineString(l) => l.lines().collect::<Vec<Line>>(),
+                Geometry::MultiLineString(ml) => ml.lines_iter().collect::<Vec<Line>>(),
+                _ => return None,
+            };
+
+            Some(MultiLineString::new(
+                lines.into_iter().map(LineString::from).collect(),
+            ))
+        })
+        .collect::<Vec<Option<MultiLineString>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(ml) => Geom::from(ml).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "multilinestring")
+}
+
 extendr_module! {
     mod explode;
     fn explode_linestrings_;
     fn explode_multilinestrings_;
+    fn explode_lines_grouped_;
 }
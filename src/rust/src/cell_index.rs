@@ -0,0 +1,195 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo_types::{coord, Geometry, LineString, Point, Polygon};
+use h3o::{CellIndex, LatLng, Resolution};
+
+use std::str::FromStr;
+
+use rayon::prelude::*;
+
+// wrapped and documented externally
+#[extendr]
+fn geohash_encode_(x: List, precision: Integers) -> Strings {
+    if !x.inherits("rs_POINT") {
+        panic!("`x` must be an `rs_POINT`")
+    }
+
+    let n_x = x.len();
+    let n_p = precision.len();
+
+    if (n_x != n_p) && (n_p != 1) {
+        panic!("`precision` must be the same length as `x` or length 1")
+    }
+
+    let precision = precision
+        .iter()
+        .cycle()
+        .take(n_x)
+        .map(|pi| {
+            if pi.is_na() {
+                return None;
+            }
+
+            match pi.inner() {
+                p @ 1..=12 => Some(p as usize),
+                _ => panic!("`precision` must be between 1 and 12"),
+            }
+        })
+        .collect::<Vec<Option<usize>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(precision.into_par_iter())
+        .map(|(xi, pi)| {
+            let pnt = match xi? {
+                Geometry::Point(p) => p,
+                _ => unreachable!(),
+            };
+
+            geohash::encode(pnt.0, pi?).ok()
+        })
+        .collect::<Vec<Option<String>>>();
+
+    res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(s) => Rstr::from(s),
+            None => Rstr::na(),
+        })
+        .collect::<Strings>()
+}
+
+#[extendr]
+/// @export
+/// @rdname geohash
+fn geohash_decode(x: Strings) -> Robj {
+    let res = x
+        .iter()
+        .map(|xi| {
+            if xi.is_na() {
+                return NULL.into_robj();
+            }
+
+            match geohash::decode(xi.as_str()) {
+                Ok((c, _, _)) => Geom::from(Point::from(c)).into_robj(),
+                Err(_) => NULL.into_robj(),
+            }
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "point")
+}
+
+#[extendr]
+/// @export
+/// @rdname geohash
+fn geohash_decode_bbox(x: Strings) -> Robj {
+    let res = x
+        .iter()
+        .map(|xi| {
+            if xi.is_na() {
+                return NULL.into_robj();
+            }
+
+            match geohash::decode_bbox(xi.as_str()) {
+                Ok(r) => Geom::from(r.to_polygon()).into_robj(),
+                Err(_) => NULL.into_robj(),
+            }
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "polygon")
+}
+
+// wrapped and documented externally
+#[extendr]
+fn h3_from_points_(x: List, resolution: Integers) -> Strings {
+    if !x.inherits("rs_POINT") {
+        panic!("`x` must be an `rs_POINT`")
+    }
+
+    let n_x = x.len();
+    let n_r = resolution.len();
+
+    if (n_x != n_r) && (n_r != 1) {
+        panic!("`resolution` must be the same length as `x` or length 1")
+    }
+
+    let resolution = resolution
+        .iter()
+        .cycle()
+        .take(n_x)
+        .map(|ri| {
+            if ri.is_na() {
+                return None;
+            }
+
+            match u8::try_from(ri.inner()).map(Resolution::try_from) {
+                Ok(Ok(r)) => Some(r),
+                _ => panic!("`resolution` must be between 0 and 15"),
+            }
+        })
+        .collect::<Vec<Option<Resolution>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(resolution.into_par_iter())
+        .map(|(xi, ri)| {
+            let pnt = match xi? {
+                Geometry::Point(p) => p,
+                _ => unreachable!(),
+            };
+
+            let ll = LatLng::new(pnt.y(), pnt.x()).ok()?;
+            Some(ll.to_cell(ri?).to_string())
+        })
+        .collect::<Vec<Option<String>>>();
+
+    res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(s) => Rstr::from(s),
+            None => Rstr::na(),
+        })
+        .collect::<Strings>()
+}
+
+#[extendr]
+/// @export
+/// @rdname h3
+fn h3_to_polygons(cells: Strings) -> Robj {
+    let res = cells
+        .iter()
+        .map(|ci| {
+            if ci.is_na() {
+                return NULL.into_robj();
+            }
+
+            let cell = match CellIndex::from_str(ci.as_str()) {
+                Ok(c) => c,
+                Err(_) => return NULL.into_robj(),
+            };
+
+            let mut ring = cell
+                .boundary()
+                .iter()
+                .map(|ll| coord! { x: ll.lng(), y: ll.lat() })
+                .collect::<LineString>();
+
+            ring.close();
+            Geom::from(Polygon::new(ring, vec![])).into_robj()
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "polygon")
+}
+
+extendr_module! {
+    mod cell_index;
+    fn geohash_encode_;
+    fn geohash_decode;
+    fn geohash_decode_bbox;
+    fn h3_from_points_;
+    fn h3_to_polygons;
+}
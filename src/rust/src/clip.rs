@@ -0,0 +1,872 @@
+use extendr_api::prelude::*;
+use sfconversions::{
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, rsgeo_type, verify_rsgeo},
+    Geom,
+};
+
+use geo::{BooleanOps, BoundingRect, Contains, CoordsIter, InteriorPoint, Intersects};
+use geo_types::{
+    coord, Coord, Geometry, GeometryCollection, Line, LineString, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon, Rect,
+};
+
+use crate::union::{coord_key, dedup_segments, split_segments};
+use crate::utils::geometry_lines;
+
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+// Liang–Barsky clipping of a single segment to a rectangle.
+// Returns the portion of the segment inside of the rectangle if any.
+// The original coordinates are returned untouched when they are not clipped
+// so that consecutive segments can be stitched back together exactly.
+fn clip_line(line: Line, rect: &Rect) -> Option<Line> {
+    let (xmin, ymin) = rect.min().x_y();
+    let (xmax, ymax) = rect.max().x_y();
+
+    let (x0, y0) = line.start.x_y();
+    let dx = line.dx();
+    let dy = line.dy();
+
+    let p = [-dx, dx, -dy, dy];
+    let q = [x0 - xmin, xmax - x0, y0 - ymin, ymax - y0];
+
+    let mut t0 = 0.0_f64;
+    let mut t1 = 1.0_f64;
+
+    for (pi, qi) in p.into_iter().zip(q) {
+        if pi == 0.0 {
+            // parallel to this edge and outside of it
+            if qi < 0.0 {
+                return None;
+            }
+        } else {
+            let t = qi / pi;
+            if pi < 0.0 {
+                if t > t1 {
+                    return None;
+                } else if t > t0 {
+                    t0 = t;
+                }
+            } else if t < t0 {
+                return None;
+            } else if t < t1 {
+                t1 = t;
+            }
+        }
+    }
+
+    let start = if t0 == 0.0 {
+        line.start
+    } else {
+        coord! { x: x0 + t0 * dx, y: y0 + t0 * dy }
+    };
+
+    let end = if t1 == 1.0 {
+        line.end
+    } else {
+        coord! { x: x0 + t1 * dx, y: y0 + t1 * dy }
+    };
+
+    Some(Line::new(start, end))
+}
+
+// clips a linestring into its parts that fall inside of the rectangle
+fn clip_linestring(x: &LineString, rect: &Rect) -> Vec<LineString> {
+    let mut res: Vec<LineString> = Vec::new();
+    let mut cur: Vec<Coord> = Vec::new();
+
+    // only keep parts that have a non-zero length
+    fn finish(cur: &mut Vec<Coord>, res: &mut Vec<LineString>) {
+        if cur.windows(2).any(|w| w[0] != w[1]) {
+            res.push(LineString::new(cur.clone()));
+        }
+        cur.clear();
+    }
+
+    for line in x.lines() {
+        match clip_line(line, rect) {
+            Some(clipped) => {
+                if cur.last() != Some(&clipped.start) {
+                    finish(&mut cur, &mut res);
+                    cur.push(clipped.start);
+                }
+
+                cur.push(clipped.end);
+
+                // the segment exits the rectangle
+                if clipped.end != line.end {
+                    finish(&mut cur, &mut res);
+                }
+            }
+            None => finish(&mut cur, &mut res),
+        }
+    }
+
+    finish(&mut cur, &mut res);
+    res
+}
+
+fn contains_coord(rect: &Rect, crd: &Coord) -> bool {
+    let (xmin, ymin) = rect.min().x_y();
+    let (xmax, ymax) = rect.max().x_y();
+    crd.x >= xmin && crd.x <= xmax && crd.y >= ymin && crd.y <= ymax
+}
+
+fn clip_polygons(x: MultiPolygon, rect: &Rect) -> Option<Geometry> {
+    let window = MultiPolygon::new(vec![rect.to_polygon()]);
+    let res = x.intersection(&window);
+
+    if res.0.is_empty() {
+        None
+    } else {
+        Some(Geometry::MultiPolygon(res))
+    }
+}
+
+fn clip_lines(x: Vec<LineString>, rect: &Rect) -> Option<Geometry> {
+    let res = x
+        .iter()
+        .flat_map(|xi| clip_linestring(xi, rect))
+        .collect::<Vec<LineString>>();
+
+    if res.is_empty() {
+        None
+    } else {
+        Some(Geometry::MultiLineString(MultiLineString::new(res)))
+    }
+}
+
+fn clip_geometry(x: Geometry, rect: &Rect) -> Option<Geometry> {
+    match x {
+        Geometry::Point(p) => {
+            if contains_coord(rect, &p.0) {
+                Some(Geometry::Point(p))
+            } else {
+                None
+            }
+        }
+        Geometry::MultiPoint(mp) => {
+            let pnts = mp
+                .into_iter()
+                .filter(|p| contains_coord(rect, &p.0))
+                .collect::<Vec<_>>();
+
+            if pnts.is_empty() {
+                None
+            } else {
+                Some(Geometry::MultiPoint(MultiPoint::new(pnts)))
+            }
+        }
+        Geometry::Line(l) => clip_lines(vec![LineString::from(l)], rect),
+        Geometry::LineString(l) => clip_lines(vec![l], rect),
+        Geometry::MultiLineString(l) => clip_lines(l.0, rect),
+        Geometry::Polygon(p) => clip_polygons(MultiPolygon::new(vec![p]), rect),
+        Geometry::MultiPolygon(p) => clip_polygons(p, rect),
+        Geometry::Rect(r) => clip_polygons(MultiPolygon::new(vec![r.to_polygon()]), rect),
+        Geometry::Triangle(t) => clip_polygons(MultiPolygon::new(vec![t.to_polygon()]), rect),
+        Geometry::GeometryCollection(gc) => {
+            let res = gc
+                .into_iter()
+                .filter_map(|g| clip_geometry(g, rect))
+                .collect::<Vec<Geometry>>();
+
+            if res.is_empty() {
+                None
+            } else {
+                Some(Geometry::GeometryCollection(GeometryCollection::from(res)))
+            }
+        }
+    }
+}
+
+#[extendr]
+/// Clip Geometries to a Rectangle
+///
+/// Clips each geometry to a rectangular window. This is the classic
+/// viewport-clipping operation used before rendering a large dataset at a
+/// fixed extent.
+///
+/// @param x an object of class `rsgeo`
+/// @param xmin,ymin,xmax,ymax a scalar numeric defining the clipping window
+///
+/// @details
+///
+/// Points are kept only when they fall inside of the window (inclusive of its
+/// boundary). Lines are clipped segment by segment using the Liang–Barsky
+/// algorithm; a line that leaves and re-enters the window results in multiple
+/// parts. Polygons are intersected with the window using boolean operations.
+/// Geometries that fall entirely outside of the window return `NULL`.
+///
+/// @export
+/// @returns
+/// An `rsgeo` vector. Points and multipoints keep their class, linear
+/// geometries are returned as an `rs_MULTILINESTRING`, and polygonal geometries
+/// are returned as an `rs_MULTIPOLYGON`.
+/// @examples
+/// lns <- geom_linestring(c(-5, 5, 5), c(0, 0, 10))
+/// clip_to_bbox(lns, -1, -1, 6, 6)
+///
+/// ply <- geom_polygon(c(0, 10, 10, 0, 0), c(0, 0, 10, 10, 0))
+/// clip_to_bbox(ply, 5, 5, 15, 15)
+fn clip_to_bbox(x: List, xmin: f64, ymin: f64, xmax: f64, ymax: f64) -> Robj {
+    let (res_vec, out_class) = clip_list(x, xmin, ymin, xmax, ymax);
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi.flatten() {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), out_class)
+}
+
+// Clips each geometry to the window. The outer `Option` is `None` for missing
+// geometries and the inner one is `None` for geometries outside of the window.
+fn clip_list(
+    x: List,
+    xmin: f64,
+    ymin: f64,
+    xmax: f64,
+    ymax: f64,
+) -> (Vec<Option<Option<Geometry>>>, &'static str) {
+    verify_rsgeo(&x);
+
+    if [xmin, ymin, xmax, ymax].iter().any(|v| v.is_nan()) {
+        panic!("`xmin`, `ymin`, `xmax`, and `ymax` cannot be missing")
+    }
+
+    if xmin > xmax || ymin > ymax {
+        panic!("`xmin` and `ymin` must be less than or equal to `xmax` and `ymax`")
+    }
+
+    let out_class = match rsgeo_type(&x).as_str() {
+        "point" => "point",
+        "multipoint" => "multipoint",
+        "linestring" | "multilinestring" => "multilinestring",
+        "polygon" | "multipolygon" => "multipolygon",
+        _ => "geometry",
+    };
+
+    let rect = Rect::new(coord! { x: xmin, y: ymin }, coord! { x: xmax, y: ymax });
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| xi.map(|g| clip_geometry(g, &rect)))
+        .collect::<Vec<Option<Option<Geometry>>>>();
+
+    (res_vec, out_class)
+}
+
+#[extendr]
+/// Clip Geometries and Track Survivors
+///
+/// Clips each geometry to a rectangular window like [`clip_to_bbox()`] and
+/// also reports which features survived the clip. The mask can be used to
+/// subset a table of attributes associated with `x`.
+///
+/// @param x an object of class `rsgeo`
+/// @param xmin,ymin,xmax,ymax a scalar numeric defining the clipping window
+///
+/// @export
+/// @returns
+/// A list with elements:
+///
+/// - `geometry`: the clipped geometries as returned by [`clip_to_bbox()`].
+/// - `keep`: a logical vector with the same length as `x`. `TRUE` when some
+///   of the geometry falls inside of the window and `FALSE` when it falls
+///   entirely outside of it. Missing geometries are `NA`.
+/// @examples
+/// lns <- geom_linestring(c(-5, 5, 20, 30), c(0, 0, 20, 20), c(1, 1, 2, 2))
+/// res <- clip_mask(lns, -1, -1, 6, 6)
+/// res
+///
+/// attrs <- data.frame(name = c("a", "b"))
+/// attrs[res$keep, , drop = FALSE]
+fn clip_mask(x: List, xmin: f64, ymin: f64, xmax: f64, ymax: f64) -> Robj {
+    let (res_vec, out_class) = clip_list(x, xmin, ymin, xmax, ymax);
+
+    let keep = res_vec
+        .iter()
+        .map(|xi| match xi {
+            Some(g) => Rbool::from(g.is_some()),
+            None => Rbool::na(),
+        })
+        .collect::<Vec<Rbool>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi.flatten() {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    list!(
+        geometry = as_rsgeo_vctr(List::from_values(res), out_class),
+        keep = Logicals::from_values(keep)
+    )
+    .into_robj()
+}
+
+// Recursively splits a polygon in half along the midline of the longer side
+// of its bounding box until each piece has at most `max_vertices` vertices.
+// `depth` guards against polygons that cannot be reduced any further.
+fn subdivide_polygon(x: Polygon, max_vertices: usize, depth: usize, res: &mut Vec<Polygon>) {
+    let bbox = match x.bounding_rect() {
+        Some(bbox) => bbox,
+        None => return,
+    };
+
+    if x.coords_count() <= max_vertices || depth == 0 {
+        res.push(x);
+        return;
+    }
+
+    let (xmin, ymin) = bbox.min().x_y();
+    let (xmax, ymax) = bbox.max().x_y();
+
+    let halves = if bbox.width() >= bbox.height() {
+        let mid = xmin + bbox.width() / 2.0;
+        [
+            Rect::new(coord! { x: xmin, y: ymin }, coord! { x: mid, y: ymax }),
+            Rect::new(coord! { x: mid, y: ymin }, coord! { x: xmax, y: ymax }),
+        ]
+    } else {
+        let mid = ymin + bbox.height() / 2.0;
+        [
+            Rect::new(coord! { x: xmin, y: ymin }, coord! { x: xmax, y: mid }),
+            Rect::new(coord! { x: xmin, y: mid }, coord! { x: xmax, y: ymax }),
+        ]
+    };
+
+    for half in halves {
+        x.intersection(&half.to_polygon())
+            .into_iter()
+            .for_each(|p| subdivide_polygon(p, max_vertices, depth - 1, res));
+    }
+}
+
+#[extendr]
+fn subdivide_(x: List, max_vertices: Integers) -> Robj {
+    if !(x.inherits("rs_POLYGON") || x.inherits("rs_MULTIPOLYGON")) {
+        panic!("`x` must be an `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    let n_x = x.len();
+    let n_v = max_vertices.len();
+
+    let max_vertices = if n_v == 1 {
+        Integers::from_values(vec![max_vertices[0]; n_x])
+    } else if n_v != n_x {
+        panic!("`max_vertices` must be the same length as `x` or length 1")
+    } else {
+        max_vertices
+    };
+
+    let max_vertices = max_vertices
+        .iter()
+        .map(|vi| {
+            if vi.is_na() {
+                None
+            } else if vi.inner() < 5 {
+                panic!("`max_vertices` must be at least 5")
+            } else {
+                Some(vi.inner() as usize)
+            }
+        })
+        .collect::<Vec<Option<usize>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(max_vertices.into_par_iter())
+        .map(|(xi, vi)| {
+            let vi = vi?;
+            let plys = match xi? {
+                Geometry::Polygon(p) => MultiPolygon::new(vec![p]),
+                Geometry::MultiPolygon(p) => p,
+                _ => unreachable!(),
+            };
+            let mut res: Vec<Polygon> = Vec::new();
+
+            plys.into_iter()
+                .for_each(|p| subdivide_polygon(p, vi, 64, &mut res));
+
+            Some(MultiPolygon::new(res))
+        })
+        .collect::<Vec<Option<MultiPolygon>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "multipolygon")
+}
+
+// Removes the parts of a linestring covered by the mask. The linestring is
+// noded with the mask's boundary and pieces whose midpoint is covered by the
+// mask are dropped. Consecutive remaining pieces are joined back together.
+fn erase_linestring(x: &LineString, mask: &MultiPolygon, mask_lines: &[Line]) -> Vec<LineString> {
+    let lines = x
+        .lines()
+        .filter(|l| l.start != l.end)
+        .collect::<Vec<Line>>();
+    let n = lines.len();
+
+    let all_lines = lines
+        .into_iter()
+        .chain(mask_lines.iter().copied())
+        .collect::<Vec<Line>>();
+
+    let mut res: Vec<LineString> = Vec::new();
+    let mut cur: Vec<Coord> = Vec::new();
+
+    for piece in split_segments(&all_lines).into_iter().take(n).flatten() {
+        let mid = coord! {
+            x: (piece.start.x + piece.end.x) / 2.0,
+            y: (piece.start.y + piece.end.y) / 2.0,
+        };
+
+        if mask.intersects(&Point::from(mid)) {
+            if cur.len() > 1 {
+                res.push(LineString::new(std::mem::take(&mut cur)));
+            }
+            cur.clear();
+        } else {
+            if cur.is_empty() {
+                cur.push(piece.start);
+            }
+            cur.push(piece.end);
+        }
+    }
+
+    if cur.len() > 1 {
+        res.push(LineString::new(cur));
+    }
+
+    res
+}
+
+fn erase_lines(x: Vec<LineString>, mask: &MultiPolygon, mask_lines: &[Line]) -> Option<Geometry> {
+    let res = x
+        .iter()
+        .flat_map(|l| erase_linestring(l, mask, mask_lines))
+        .collect::<Vec<LineString>>();
+
+    if res.is_empty() {
+        None
+    } else {
+        Some(Geometry::MultiLineString(MultiLineString::new(res)))
+    }
+}
+
+fn erase_polygons(x: MultiPolygon, mask: &MultiPolygon) -> Option<Geometry> {
+    let res = x.difference(mask);
+
+    if res.0.is_empty() {
+        None
+    } else {
+        Some(Geometry::MultiPolygon(res))
+    }
+}
+
+fn erase_geometry(x: Geometry, mask: &MultiPolygon, mask_lines: &[Line]) -> Option<Geometry> {
+    match x {
+        Geometry::Point(p) => {
+            if mask.intersects(&p) {
+                None
+            } else {
+                Some(Geometry::Point(p))
+            }
+        }
+        Geometry::MultiPoint(mp) => {
+            let pnts = mp
+                .into_iter()
+                .filter(|p| !mask.intersects(p))
+                .collect::<Vec<_>>();
+
+            if pnts.is_empty() {
+                None
+            } else {
+                Some(Geometry::MultiPoint(MultiPoint::new(pnts)))
+            }
+        }
+        Geometry::Line(l) => erase_lines(vec![LineString::from(l)], mask, mask_lines),
+        Geometry::LineString(l) => erase_lines(vec![l], mask, mask_lines),
+        Geometry::MultiLineString(l) => erase_lines(l.0, mask, mask_lines),
+        Geometry::Polygon(p) => erase_polygons(MultiPolygon::new(vec![p]), mask),
+        Geometry::MultiPolygon(p) => erase_polygons(p, mask),
+        Geometry::Rect(r) => erase_polygons(MultiPolygon::new(vec![r.to_polygon()]), mask),
+        Geometry::Triangle(t) => erase_polygons(MultiPolygon::new(vec![t.to_polygon()]), mask),
+        Geometry::GeometryCollection(gc) => {
+            let res = gc
+                .into_iter()
+                .filter_map(|g| erase_geometry(g, mask, mask_lines))
+                .collect::<Vec<Geometry>>();
+
+            if res.is_empty() {
+                None
+            } else {
+                Some(Geometry::GeometryCollection(GeometryCollection::from(res)))
+            }
+        }
+    }
+}
+
+// converts a geometry to the output type of `erase()` without changing it
+fn as_erased(x: Geometry) -> Option<Geometry> {
+    erase_geometry(x, &MultiPolygon::new(vec![]), &[])
+}
+
+#[extendr]
+/// Erase Geometries with a Mask
+///
+/// Removes the parts of each geometry that are covered by the corresponding
+/// mask polygon. This is the complement of clipping and is useful for
+/// removing everything within an area such as a body of water.
+///
+/// @param x an object of class `rsgeo`
+/// @param mask an object of class `rs_POLYGON` or `rs_MULTIPOLYGON` of length 1
+///   or the same length as `x`
+///
+/// @details
+///
+/// Points are removed when they intersect the mask, including its boundary.
+/// Lines are split where they cross the boundary of the mask and the parts
+/// inside of or along the boundary of the mask are removed. Polygons are
+/// erased using boolean difference. Geometries that are entirely covered by
+/// the mask return `NULL`. A missing mask erases nothing.
+///
+/// @export
+/// @returns
+/// An `rsgeo` vector. Points and multipoints keep their class, linear
+/// geometries are returned as an `rs_MULTILINESTRING`, and polygonal geometries
+/// are returned as an `rs_MULTIPOLYGON`.
+/// @examples
+/// mask <- geom_polygon(c(2, 4, 4, 2, 2), c(-1, -1, 1, 1, -1))
+/// lns <- geom_linestring(c(0, 10), c(0, 0))
+/// erase(lns, mask)
+///
+/// ply <- geom_polygon(c(0, 10, 10, 0, 0), c(0, 0, 10, 10, 0))
+/// erase(ply, mask)
+fn erase(x: List, mask: List) -> Robj {
+    verify_rsgeo(&x);
+
+    if !(mask.inherits("rs_POLYGON") || mask.inherits("rs_MULTIPOLYGON")) {
+        panic!("`mask` must be an `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    let n_x = x.len();
+    let n_m = mask.len();
+
+    if (n_m != n_x) && (n_m != 1) {
+        panic!("`mask` must be the same length as `x` or length 1")
+    }
+
+    let out_class = match rsgeo_type(&x).as_str() {
+        "point" => "point",
+        "multipoint" => "multipoint",
+        "linestring" | "multilinestring" => "multilinestring",
+        "polygon" | "multipolygon" => "multipolygon",
+        _ => "geometry",
+    };
+
+    let masks = geometry_from_list(mask)
+        .into_iter()
+        .map(|mi| match mi? {
+            Geometry::Polygon(p) => Some(MultiPolygon::new(vec![p])),
+            Geometry::MultiPolygon(p) => Some(p),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<Option<MultiPolygon>>>();
+
+    let masks = if n_m == 1 {
+        vec![masks[0].clone(); n_x]
+    } else {
+        masks
+    };
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(masks.into_par_iter())
+        .map(|(xi, mi)| match mi {
+            Some(m) => {
+                let mask_lines = geometry_lines(&Geometry::MultiPolygon(m.clone()));
+                erase_geometry(xi?, &m, &mask_lines)
+            }
+            None => as_erased(xi?),
+        })
+        .collect::<Vec<Option<Geometry>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), out_class)
+}
+
+// twice the signed area of a closed ring which is positive when counter-clockwise
+fn ring_area(x: &[Coord]) -> f64 {
+    x.windows(2)
+        .map(|w| w[0].x * w[1].y - w[1].x * w[0].y)
+        .sum()
+}
+
+// Finds the bounded faces of a set of noded segments. Dangling segments
+// are removed first as they can't be part of a face. Faces are traced by
+// always taking the next edge clockwise so that each bounded face is
+// returned as a counter-clockwise ring.
+fn polygonize(x: &[Line]) -> Vec<LineString> {
+    let mut node_ids: HashMap<(u64, u64), usize> = HashMap::new();
+    let mut nodes: Vec<Coord> = Vec::new();
+    let mut edges: Vec<(usize, usize)> = Vec::with_capacity(x.len());
+
+    for l in x {
+        let ids = [l.start, l.end].map(|c| {
+            *node_ids.entry(coord_key(&c)).or_insert_with(|| {
+                nodes.push(c);
+                nodes.len() - 1
+            })
+        });
+        edges.push((ids[0], ids[1]));
+    }
+
+    let mut degree = vec![0_usize; nodes.len()];
+    edges.iter().for_each(|(a, b)| {
+        degree[*a] += 1;
+        degree[*b] += 1;
+    });
+
+    // remove dangles until none are left
+    let mut removed = vec![false; edges.len()];
+    loop {
+        let mut changed = false;
+
+        for (i, (a, b)) in edges.iter().enumerate() {
+            if !removed[i] && (degree[*a] == 1 || degree[*b] == 1) {
+                removed[i] = true;
+                degree[*a] -= 1;
+                degree[*b] -= 1;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    // the neighbors of each node sorted counter-clockwise
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    for (i, (a, b)) in edges.iter().enumerate() {
+        if !removed[i] {
+            out[*a].push(*b);
+            out[*b].push(*a);
+        }
+    }
+
+    for (v, nbrs) in out.iter_mut().enumerate() {
+        let o = nodes[v];
+        let angle = |w: &usize| (nodes[*w].y - o.y).atan2(nodes[*w].x - o.x);
+        nbrs.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+    }
+
+    let mut visited: HashSet<(usize, usize)> = HashSet::new();
+    let mut res: Vec<LineString> = Vec::new();
+
+    for (u, nbrs) in out.iter().enumerate() {
+        for &v in nbrs {
+            if visited.contains(&(u, v)) {
+                continue;
+            }
+
+            let mut ring = vec![nodes[u]];
+            let (mut a, mut b) = (u, v);
+
+            while visited.insert((a, b)) {
+                ring.push(nodes[b]);
+                let nb = &out[b];
+                let pos = nb.iter().position(|&w| w == a).unwrap();
+                let next = nb[(pos + nb.len() - 1) % nb.len()];
+                a = b;
+                b = next;
+            }
+
+            // the unbounded face is traced clockwise
+            if ring_area(&ring) > 0.0 {
+                res.push(LineString::new(ring));
+            }
+        }
+    }
+
+    res
+}
+
+// Splits a polygon by noding its rings with the blade and polygonizing the
+// result. Only the parts of the blade inside of the polygon are used. Holes
+// that the blade doesn't touch are added back to the piece containing them.
+fn split_polygon_by(x: Polygon, blade: &MultiLineString) -> Vec<Polygon> {
+    if !x.intersects(blade) {
+        return vec![x];
+    }
+
+    let (touched, untouched): (Vec<&LineString>, Vec<&LineString>) =
+        x.interiors().iter().partition(|r| r.intersects(blade));
+
+    let is_segment = |l: &Line| l.start != l.end;
+
+    let ring_lines = std::iter::once(x.exterior())
+        .chain(touched)
+        .flat_map(|r| r.lines())
+        .filter(is_segment)
+        .collect::<Vec<Line>>();
+
+    let n_ring = ring_lines.len();
+
+    let all_lines = ring_lines
+        .into_iter()
+        .chain(blade.iter().flat_map(|l| l.lines()).filter(is_segment))
+        .collect::<Vec<Line>>();
+
+    let ply = &x;
+    let edges = split_segments(&all_lines)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, pieces)| {
+            pieces.into_iter().filter(move |l| {
+                let mid = coord! {
+                    x: (l.start.x + l.end.x) / 2.0,
+                    y: (l.start.y + l.end.y) / 2.0,
+                };
+                i < n_ring || ply.contains(&Point::from(mid))
+            })
+        })
+        .collect::<Vec<Line>>();
+
+    polygonize(&dedup_segments(edges))
+        .into_iter()
+        .filter_map(|ring| {
+            let face = Polygon::new(ring, vec![]);
+
+            // faces inside of holes are not part of the polygon
+            if !x.contains(&face.interior_point()?) {
+                return None;
+            }
+
+            let holes = untouched
+                .iter()
+                .filter(|h| face.contains(&Point::from(h.0[0])))
+                .map(|h| (*h).clone())
+                .collect::<Vec<LineString>>();
+
+            Some(Polygon::new(face.exterior().clone(), holes))
+        })
+        .collect()
+}
+
+#[extendr]
+/// Split Polygons by a LineString
+///
+/// Splits each polygon into multiple polygons using a cutting LineString, like
+/// PostGIS' `ST_Split`. This is useful for dividing parcels and regions.
+///
+/// @param x an object of class `rs_POLYGON` or `rs_MULTIPOLYGON`
+/// @param blade an object of class `rs_LINESTRING` or `rs_MULTILINESTRING` of
+///   length 1 or the same length as `x`
+///
+/// @details
+///
+/// The rings of each polygon are noded with the blade and the resulting
+/// segments are assembled into polygons. Only the parts of the blade inside of
+/// a polygon are used. A blade that does not fully cross a polygon does not
+/// split it and the original polygon is returned.
+///
+/// @export
+/// @returns
+/// An object of class `rs_MULTIPOLYGON` where each element contains the pieces
+/// of the corresponding polygon in `x`. If the blade is missing the polygon is
+/// returned unsplit.
+/// @examples
+/// ply <- geom_polygon(c(0, 10, 10, 0, 0), c(0, 0, 10, 10, 0))
+/// blade <- geom_linestring(c(5, 5), c(-1, 11))
+/// split_polygon(ply, blade)
+fn split_polygon(x: List, blade: List) -> Robj {
+    if !(x.inherits("rs_POLYGON") || x.inherits("rs_MULTIPOLYGON")) {
+        panic!("`x` must be an `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    } else if !(blade.inherits("rs_LINESTRING") || blade.inherits("rs_MULTILINESTRING")) {
+        panic!("`blade` must be an `rs_LINESTRING` or `rs_MULTILINESTRING`")
+    }
+
+    let n_x = x.len();
+    let n_b = blade.len();
+
+    if (n_b != n_x) && (n_b != 1) {
+        panic!("`blade` must be the same length as `x` or length 1")
+    }
+
+    let blades = geometry_from_list(blade)
+        .into_iter()
+        .map(|bi| match bi? {
+            Geometry::LineString(l) => Some(MultiLineString::new(vec![l])),
+            Geometry::MultiLineString(l) => Some(l),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<Option<MultiLineString>>>();
+
+    let blades = if n_b == 1 {
+        vec![blades[0].clone(); n_x]
+    } else {
+        blades
+    };
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(blades.into_par_iter())
+        .map(|(xi, bi)| {
+            let plys = match xi? {
+                Geometry::Polygon(p) => MultiPolygon::new(vec![p]),
+                Geometry::MultiPolygon(p) => p,
+                _ => unreachable!(),
+            };
+
+            match bi {
+                Some(b) => Some(MultiPolygon::new(
+                    plys.into_iter()
+                        .flat_map(|p| split_polygon_by(p, &b))
+                        .collect(),
+                )),
+                None => Some(plys),
+            }
+        })
+        .collect::<Vec<Option<MultiPolygon>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "multipolygon")
+}
+
+extendr_module! {
+    mod clip;
+    fn clip_to_bbox;
+    fn clip_mask;
+    fn subdivide_;
+    fn split_polygon;
+    fn erase;
+}
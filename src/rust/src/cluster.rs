@@ -0,0 +1,161 @@
+use extendr_api::prelude::*;
+use sfconversions::geometry_from_list;
+
+use geo::{EuclideanDistance, HaversineDistance};
+use geo_types::Point;
+
+use rayon::prelude::*;
+use rstar::primitives::GeomWithData;
+use rstar::{RTree, AABB};
+
+// the mean radius of the earth in meters as used by `HaversineDistance`
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+// Clusters points using DBSCAN. `window` returns an envelope containing every
+// point that could be within `eps` of a point and `within` determines if two
+// points are within `eps` of each other. Noise is labeled 0.
+fn dbscan_impl<W, D>(x: List, min_pts: i32, window: W, within: D) -> Integers
+where
+    W: Fn(&Point) -> AABB<[f64; 2]> + Sync,
+    D: Fn(&Point, &Point) -> bool + Sync,
+{
+    if !x.inherits("rs_POINT") {
+        panic!("`x` must be an `rs_POINT`")
+    }
+
+    if min_pts < 1 {
+        panic!("`min_pts` must be a positive integer")
+    }
+
+    let min_pts = min_pts as usize;
+
+    let pnts = geometry_from_list(x)
+        .into_iter()
+        .map(|xi| xi.map(|g| Point::try_from(g).unwrap()))
+        .collect::<Vec<Option<Point>>>();
+
+    let n = pnts.len();
+
+    let tree = RTree::bulk_load(
+        pnts.iter()
+            .enumerate()
+            .filter_map(|(i, p)| p.map(|p| GeomWithData::new([p.x(), p.y()], i)))
+            .collect::<Vec<GeomWithData<[f64; 2], usize>>>(),
+    );
+
+    // the neighborhood of each point includes the point itself
+    let neighbors = pnts
+        .par_iter()
+        .map(|p| match p {
+            Some(p) => tree
+                .locate_in_envelope_intersecting(&window(p))
+                .filter(|cnd| within(p, &Point::from(*cnd.geom())))
+                .map(|cnd| cnd.data)
+                .collect::<Vec<usize>>(),
+            None => Vec::new(),
+        })
+        .collect::<Vec<Vec<usize>>>();
+
+    // -1 marks points that haven't been visited
+    let mut labels = vec![-1_i32; n];
+    let mut cluster = 0_i32;
+
+    for i in 0..n {
+        if pnts[i].is_none() || labels[i] != -1 {
+            continue;
+        }
+
+        if neighbors[i].len() < min_pts {
+            labels[i] = 0;
+            continue;
+        }
+
+        cluster += 1;
+        labels[i] = cluster;
+
+        // expand the cluster from every core point that is reached
+        let mut queue = neighbors[i].clone();
+
+        while let Some(j) = queue.pop() {
+            // noise that is reachable from a core point is a border point
+            if labels[j] == 0 {
+                labels[j] = cluster;
+            }
+
+            if labels[j] != -1 {
+                continue;
+            }
+
+            labels[j] = cluster;
+
+            if neighbors[j].len() >= min_pts {
+                queue.extend(neighbors[j].iter().copied());
+            }
+        }
+    }
+
+    labels
+        .into_iter()
+        .zip(pnts.iter())
+        .map(|(l, p)| {
+            if p.is_some() {
+                Rint::from(l)
+            } else {
+                Rint::na()
+            }
+        })
+        .collect::<Integers>()
+}
+
+fn check_eps(eps: f64) {
+    if !eps.is_finite() || eps < 0.0 {
+        panic!("`eps` must be a non-negative number")
+    }
+}
+
+#[extendr]
+fn dbscan_(x: List, eps: f64, min_pts: i32) -> Integers {
+    check_eps(eps);
+
+    let window =
+        |p: &Point| AABB::from_corners([p.x() - eps, p.y() - eps], [p.x() + eps, p.y() + eps]);
+    let within = |p: &Point, q: &Point| p.euclidean_distance(q) <= eps;
+
+    dbscan_impl(x, min_pts, window, within)
+}
+
+#[extendr]
+fn dbscan_haversine_(x: List, eps: f64, min_pts: i32) -> Integers {
+    check_eps(eps);
+
+    // the change in latitude and longitude in degrees equivalent to `eps`.
+    // Near the poles, or across the antimeridian, the whole range of
+    // longitudes is searched.
+    let dlat = (eps / EARTH_RADIUS).to_degrees();
+
+    let window = move |p: &Point| {
+        let dlon = dlat / p.y().to_radians().cos();
+
+        let (xmin, xmax) = if p.y().abs() + dlat >= 90.0
+            || !dlon.is_finite()
+            || p.x() - dlon < -180.0
+            || p.x() + dlon > 180.0
+        {
+            (f64::NEG_INFINITY, f64::INFINITY)
+        } else {
+            (p.x() - dlon, p.x() + dlon)
+        };
+
+        AABB::from_corners([xmin, p.y() - dlat], [xmax, p.y() + dlat])
+    };
+
+    let within = |p: &Point, q: &Point| p.haversine_distance(q) <= eps;
+
+    dbscan_impl(x, min_pts, window, within)
+}
+
+extendr_module! {
+    mod cluster;
+    fn dbscan_;
+    fn dbscan_haversine_;
+}
@@ -62,19 +62,27 @@ fn geom_multipoint_(x: Doubles, y: Doubles, id: Integers) -> Robj {
 
     // iterate through everything and create points
     for ((xi, yi), idx) in x.iter().zip(y.iter()).zip(id.iter()) {
+        // every feature has an entry even if all of its coordinates are missing
+        let pnts = map_mpnts.entry(idx.inner()).or_default();
+
         // check to see if xi and yi are real values / non-missing
         if xi.is_real() && yi.is_real() {
-            let pnt = point!(x: xi.inner(), y: yi.inner());
-
-            map_mpnts.entry(idx.inner()).or_insert(Vec::new()).push(pnt);
+            pnts.push(point!(x: xi.inner(), y: yi.inner()));
         }
     }
 
     // iterate through the hash map to create a new multipoint from each
+    // features without any coordinates are missing
     let res_vec = map_mpnts
-        .into_iter()
-        .map(|(_, pts)| Geom::from(MultiPoint::new(pts)))
-        .collect::<Vec<Geom>>();
+        .into_values()
+        .map(|pts| {
+            if pts.is_empty() {
+                NULL.into_robj()
+            } else {
+                Geom::from(MultiPoint::new(pts)).into_robj()
+            }
+        })
+        .collect::<Vec<Robj>>();
 
     // create multipoint vector
     List::from_values(res_vec)
@@ -104,19 +112,27 @@ fn geom_linestring_(x: Doubles, y: Doubles, id: Integers) -> Robj {
 
     // iterate through everything and create points
     for ((xi, yi), idx) in x.iter().zip(y.iter()).zip(id.iter()) {
+        // every feature has an entry even if all of its coordinates are missing
+        let crds = map_mpnts.entry(idx.inner()).or_default();
+
         // check to see if xi and yi are real values / non-missing
         if xi.is_real() && yi.is_real() {
-            let pnt = coord!(x: xi.inner(), y: yi.inner());
-
-            map_mpnts.entry(idx.inner()).or_insert(Vec::new()).push(pnt);
+            crds.push(coord!(x: xi.inner(), y: yi.inner()));
         }
     }
 
-    // iterate through the hash map to create a new multipoint from each
+    // iterate through the hash map to create a new linestring from each
+    // features without any coordinates are missing
     let res_vec = map_mpnts
-        .into_iter()
-        .map(|(_, pts)| Geom::from(LineString::new(pts)))
-        .collect::<Vec<Geom>>();
+        .into_values()
+        .map(|pts| {
+            if pts.is_empty() {
+                NULL.into_robj()
+            } else {
+                Geom::from(LineString::new(pts)).into_robj()
+            }
+        })
+        .collect::<Vec<Robj>>();
 
     // create multipoint vector
     List::from_values(res_vec)
@@ -154,23 +170,35 @@ fn geom_polygon_(x: Doubles, y: Doubles, id: Integers, ring: Integers) -> Robj {
 
     // iterate through everything and create points
     for (((xi, yi), idx), ring_idx) in x.iter().zip(y.iter()).zip(id.iter()).zip(ring.iter()) {
+        // every feature has an entry even if all of its coordinates are missing
+        let rings = map_rings.entry(ring_idx.inner()).or_default();
+
         // check to see if xi and yi are real values / non-missing
         if xi.is_real() && yi.is_real() {
             let pnt = coord!(x: xi.inner(), y: yi.inner());
 
-            map_rings
-                .entry(ring_idx.inner())
-                .or_insert(BTreeMap::new())
-                .entry(idx.inner())
-                .or_insert(Vec::new())
-                .push(pnt);
+            rings.entry(idx.inner()).or_default().push(pnt);
         }
     }
 
+    // Polygon::new() closes rings so unclosed rings are recorded before
+    // they are closed to let the caller warn or error about them
+    let mut unclosed: Vec<i32> = Vec::new();
+
     // iterate through the hash map to create polygons from each ring
+    // features without any coordinates are missing
     let res_vec = map_rings
-        .into_iter()
-        .map(|(_, ring_points)| {
+        .into_values()
+        .enumerate()
+        .map(|(i, ring_points)| {
+            if ring_points.is_empty() {
+                return NULL.into_robj();
+            }
+
+            if ring_points.values().any(|pts| pts.first() != pts.last()) {
+                unclosed.push(i as i32 + 1);
+            }
+
             let polygons = ring_points
                 .into_iter()
                 .map(|(_, pts)| LineString::new(pts))
@@ -187,14 +215,20 @@ fn geom_polygon_(x: Doubles, y: Doubles, id: Integers, ring: Integers) -> Robj {
                 Polygon::new(polygons.into_iter().next().unwrap(), vec![])
             };
 
-            Geom::from(poly)
+            Geom::from(poly).into_robj()
         })
-        .collect::<Vec<Geom>>();
+        .collect::<Vec<Robj>>();
 
     // create multipolygon vector
-    List::from_values(res_vec)
+    let res = List::from_values(res_vec)
         .set_class(geom_class("polygon"))
-        .unwrap()
+        .unwrap();
+
+    if unclosed.is_empty() {
+        res
+    } else {
+        res.set_attrib("unclosed", unclosed).unwrap()
+    }
 }
 
 #[extendr]
@@ -224,6 +258,36 @@ fn geom_line(x: List, y: List) -> Robj {
     as_rsgeo_vctr(res, "linestring")
 }
 
+#[extendr]
+/// Identify Missing Geometries
+///
+/// Identifies which elements of a geometry vector are missing. Missing
+/// geometries can be removed before calling functions that require every
+/// geometry to be present.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @export
+/// @returns
+/// A logical vector of the same length as `x` that is `TRUE` where the
+/// geometry is missing.
+/// @examples
+/// pnts <- geom_point(c(1, NA, 3), c(1, 2, 3))
+/// geom_is_na(pnts)
+/// pnts[!geom_is_na(pnts)]
+fn geom_is_na(x: List) -> Logicals {
+    if !x.inherits("rsgeo") {
+        panic!("`x` must be of class `rsgeo`")
+    }
+
+    let res = x
+        .iter()
+        .map(|(_, xi)| Rbool::from(xi.is_null()))
+        .collect::<Vec<Rbool>>();
+
+    Logicals::from_values(res)
+}
+
 extendr_module! {
     mod construction;
     fn geom_point_;
@@ -231,4 +295,5 @@ extendr_module! {
     fn geom_linestring_;
     fn geom_polygon_;
     fn geom_line;
+    fn geom_is_na;
 }
@@ -54,15 +54,13 @@ fn coord_last(x: List) -> Robj {
             if xi.is_null() {
                 NULL.into_robj()
             } else {
-                let pnt: Point = <&Geom>::from_robj(&xi)
-                    .unwrap()
-                    .geom
-                    .coords_iter()
-                    .last()
-                    .unwrap()
-                    .into();
+                let crd = <&Geom>::from_robj(&xi).unwrap().geom.coords_iter().last();
 
-                pnt.into_geom().into_robj()
+                // empty geometries have no coordinates
+                match crd {
+                    Some(c) => Point::from(c).into_geom().into_robj(),
+                    None => NULL.into_robj(),
+                }
             }
         })
         .collect::<Vec<Robj>>();
@@ -87,14 +85,13 @@ fn coord_first(x: List) -> Robj {
             if xi.is_null() {
                 NULL.into_robj()
             } else {
-                let pnt: Point = <&Geom>::from_robj(&xi)
-                    .unwrap()
-                    .geom
-                    .coords_iter()
-                    .next()
-                    .unwrap()
-                    .into();
-                pnt.into_geom().into_robj()
+                let crd = <&Geom>::from_robj(&xi).unwrap().geom.coords_iter().next();
+
+                // empty geometries have no coordinates
+                match crd {
+                    Some(c) => Point::from(c).into_geom().into_robj(),
+                    None => NULL.into_robj(),
+                }
             }
         })
         .collect::<Vec<Robj>>();
@@ -127,7 +124,7 @@ fn coord_n_(x: List, n: Integers) -> Robj {
         .into_iter()
         .zip(n.iter())
         .map(|((_, xi), ni)| {
-            if xi.is_null() || ni.is_na() {
+            if xi.is_null() || ni.is_na() || ni.inner() < 1 {
                 NULL.into_robj()
             } else {
                 let crd = <&Geom>::from_robj(&xi)
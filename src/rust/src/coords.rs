@@ -1,7 +1,9 @@
 use extendr_api::prelude::*;
 use geo::CoordsIter;
 use geo_types::*;
-use sfconversions::Geom;
+use sfconversions::{geometry_from_list, vctrs::verify_rsgeo, Geom};
+
+use crate::utils::new_data_frame;
 
 // TODO - missing value handling for coordinate conversions
 #[extendr]
@@ -149,7 +151,7 @@ fn polygon_coords(x: Polygon) -> Vec<((f64, f64), i32)> {
         .enumerate()
         .flat_map(|(i, ring)| {
             ring.coords_iter()
-                .map(|c| (c.x_y(), (i + 1) as i32))
+                .map(|c| (c.x_y(), (i + 2) as i32))
                 .collect::<Vec<((f64, f64), i32)>>()
         })
         .collect::<Vec<((f64, f64), i32)>>();
@@ -202,6 +204,191 @@ fn multipolygon_coords(x: MultiPolygon) -> Vec<(((f64, f64), i32), i32)> {
         .collect::<Vec<(((f64, f64), i32), i32)>>()
 }
 
+#[extendr]
+/// Convert Points to a Matrix
+///
+/// Converts an `rs_POINT` vector into a numeric matrix of coordinates in a
+/// single pass. This is the inverse of `geom_point()`.
+///
+/// @param x an object of class `rs_POINT`
+///
+/// @export
+/// @returns
+/// A numeric matrix with columns `x` and `y` and a row for each element of `x`.
+/// Missing points result in a row of `NA` values.
+/// @examples
+/// pnts <- geom_point(c(1, NA, 3), c(4, 5, 6))
+/// points_to_matrix(pnts)
+fn points_to_matrix(x: List) -> Robj {
+    if !x.inherits("rs_POINT") {
+        panic!("`x` must be of class `rs_POINT`")
+    }
+
+    let n = x.len();
+    let mut res_vec = vec![Rfloat::na(); n * 2];
+
+    for (i, (_, xi)) in x.into_iter().enumerate() {
+        if let Ok(g) = <&Geom>::from_robj(&xi) {
+            if let Geometry::Point(p) = &g.geom {
+                res_vec[i] = p.x().into();
+                res_vec[i + n] = p.y().into();
+            }
+        }
+    }
+
+    let dimnames = list!(NULL, Strings::from_values(["x", "y"]));
+
+    Doubles::from_values(res_vec)
+        .into_robj()
+        .set_attrib("dim", [n as i32, 2])
+        .unwrap()
+        .set_attrib("dimnames", dimnames)
+        .unwrap()
+}
+
+// A path as `(piece, ring, coords)` where `piece` is the component of a
+// multi-geometry and `ring` is the ring of a polygon.
+type Path = (i32, i32, Vec<Coord>);
+
+fn geometry_paths(x: Geometry) -> Vec<Path> {
+    let polygon_paths = |piece: i32, p: Polygon| {
+        let (exterior, interiors) = p.into_inner();
+        std::iter::once(exterior)
+            .chain(interiors)
+            .enumerate()
+            .map(move |(ring, r)| (piece, ring as i32 + 1, r.0))
+            .collect::<Vec<Path>>()
+    };
+
+    match x {
+        Geometry::Point(p) => vec![(1, 1, vec![p.0])],
+        Geometry::MultiPoint(mp) => mp
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| (i as i32 + 1, 1, vec![p.0]))
+            .collect(),
+        Geometry::Line(l) => vec![(1, 1, vec![l.start, l.end])],
+        Geometry::LineString(l) => vec![(1, 1, l.0)],
+        Geometry::MultiLineString(ml) => ml
+            .into_iter()
+            .enumerate()
+            .map(|(i, l)| (i as i32 + 1, 1, l.0))
+            .collect(),
+        Geometry::Polygon(p) => polygon_paths(1, p),
+        Geometry::MultiPolygon(mp) => mp
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, p)| polygon_paths(i as i32 + 1, p))
+            .collect(),
+        Geometry::Rect(r) => polygon_paths(1, r.to_polygon()),
+        Geometry::Triangle(t) => polygon_paths(1, t.to_polygon()),
+        Geometry::GeometryCollection(gc) => gc
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, g)| {
+                geometry_paths(g)
+                    .into_iter()
+                    .map(move |(_, ring, crds)| (i as i32 + 1, ring, crds))
+            })
+            .collect(),
+    }
+}
+
+#[extendr]
+/// Fortify Geometries for ggplot2
+///
+/// Extracts the coordinates of geometries into a long `data.frame` shaped for
+/// ggplot2's `geom_point()`, `geom_path()`, and `geom_polygon()`. This makes
+/// it possible to plot rsgeo geometries with ggplot2 without converting them
+/// to sf.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @details
+///
+/// Each LineString and each ring of a polygon is a separate path identified
+/// by `group`. Use `aes(group = group)` with `geom_path()` to draw lines and
+/// polygon boundaries. To fill polygons with holes, each polygon must be
+/// drawn as a single group with its rings as subgroups e.g.
+/// `aes(group = interaction(id, piece), subgroup = group)` with
+/// `geom_polygon()`.
+///
+/// Missing and empty geometries are ignored.
+///
+/// @export
+/// @returns
+/// A `data.frame` with a row for each coordinate and columns:
+///
+/// - `id`: the index of the geometry in `x`
+/// - `piece`: the component of a multi-geometry or geometry collection. `1`
+///   for single geometries.
+/// - `ring`: the ring of a polygon where `1` is the exterior ring and
+///   larger values are holes. `1` for other geometries.
+/// - `group`: a unique identifier of each path across all geometries
+/// - `order`: the position of the coordinate along its path
+/// - `x`, `y`: the coordinates
+/// @examples
+/// ply <- geom_polygon(
+///   c(0, 4, 4, 0, 0, 1, 2, 2, 1, 1),
+///   c(0, 0, 4, 4, 0, 1, 1, 2, 2, 1),
+///   ring = rep(1:2, each = 5)
+/// )
+/// df <- fortify_coords(ply)
+/// df
+///
+/// if (rlang::is_installed("ggplot2")) {
+///   ggplot2::ggplot(df) +
+///     ggplot2::geom_polygon(
+///       ggplot2::aes(x, y, group = interaction(id, piece), subgroup = group)
+///     )
+/// }
+fn fortify_coords(x: List) -> Robj {
+    verify_rsgeo(&x);
+
+    let mut id: Vec<i32> = Vec::new();
+    let mut piece: Vec<i32> = Vec::new();
+    let mut ring: Vec<i32> = Vec::new();
+    let mut group: Vec<i32> = Vec::new();
+    let mut order: Vec<i32> = Vec::new();
+    let mut xs: Vec<f64> = Vec::new();
+    let mut ys: Vec<f64> = Vec::new();
+
+    let paths = geometry_from_list(x)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, xi)| Some((i, xi?)))
+        .flat_map(|(i, g)| geometry_paths(g).into_iter().map(move |p| (i, p)))
+        .filter(|(_, (.., crds))| !crds.is_empty());
+
+    for (g, (i, (pi, ri, crds))) in paths.enumerate() {
+        for (j, c) in crds.into_iter().enumerate() {
+            id.push(i as i32 + 1);
+            piece.push(pi);
+            ring.push(ri);
+            group.push(g as i32 + 1);
+            order.push(j as i32 + 1);
+            xs.push(c.x);
+            ys.push(c.y);
+        }
+    }
+
+    let n = xs.len();
+
+    new_data_frame(
+        ["id", "piece", "ring", "group", "order", "x", "y"],
+        [
+            Integers::from_values(id).into_robj(),
+            Integers::from_values(piece).into_robj(),
+            Integers::from_values(ring).into_robj(),
+            Integers::from_values(group).into_robj(),
+            Integers::from_values(order).into_robj(),
+            Doubles::from_values(xs).into_robj(),
+            Doubles::from_values(ys).into_robj(),
+        ],
+        n,
+    )
+}
+
 extendr_module! {
     mod coords;
     fn point_to_coords;
@@ -210,4 +397,6 @@ extendr_module! {
     fn multilinestring_to_coords;
     fn polygon_to_coords;
     fn multipolygon_to_coords;
+    fn points_to_matrix;
+    fn fortify_coords;
 }
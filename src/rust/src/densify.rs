@@ -1,12 +1,17 @@
 use extendr_api::prelude::*;
 
-use geo::{Densify, DensifyHaversine};
-use geo_types::Geometry;
+use geo::{CoordsIter, Densify, DensifyHaversine, EuclideanLength};
+use geo_types::{Coord, Geometry, LineString, MultiLineString, MultiPolygon, Polygon};
 use sfconversions::{
+    geometry_from_list,
     vctrs::{as_rsgeo_vctr, rsgeo_type},
     Geom, IntoGeom,
 };
 
+use crate::construction::IsReal;
+use crate::utils::geometry_lines;
+use rayon::prelude::*;
+
 #[extendr]
 /// Densify linear geometries
 ///
@@ -22,15 +27,34 @@ use sfconversions::{
 /// `max_distance` expects meters for `densify_haversine()` whereas
 /// `densify_euclidean()` expects the units of the geometry.
 ///
+/// `densify()` calls `densify_haversine()` when `geodesic = TRUE` and
+/// `densify_euclidean()` otherwise so that the algorithm can be chosen with
+/// an argument.
+///
 /// Be sure to use the appropriate densification function based on
 /// the type of geometries you have. rsgeo does not check if your coordinates
 /// are geographic or planar. It is up to you to choose the correct algorithm.
 ///
+/// `densify_threshold()` densifies using Euclidean distance but only splits
+/// segments that are longer than `min_segment`. Segments that are already
+/// short are left untouched so that detailed portions of a geometry aren't
+/// bloated with unnecessary coordinates.
+///
+/// `densify_max()` densifies using Euclidean distance but never adds more
+/// coordinates than `max_total`. When densifying by `max_distance` would
+/// exceed `max_total`, the geometry is densified using the smallest distance
+/// that stays within the limit and a warning is emitted. This protects
+/// against creating millions of coordinates by accident when `max_distance`
+/// is very small.
+///
 /// @examples
 ///
 /// line <- geom_linestring(1:10, 10:1)
 /// densify_euclidean(line, 0.5)
 /// densify_haversine(line, 100000)
+/// densify(line, 100000, geodesic = TRUE)
+/// densify_max(line, 0.01, 100)
+/// densify_threshold(geom_linestring(c(0, 0.1, 10), c(0, 0, 0)), 1, 2)
 ///
 /// @export
 /// @rdname densify
@@ -59,16 +83,20 @@ fn densify_euclidean(x: List, max_distance: Doubles) -> Robj {
         .into_iter()
         .zip(max_distance.iter())
         .map(|((_, xi), md)| {
+            if xi.is_null() || !md.is_real() {
+                return NULL.into_robj();
+            }
+
             let xi = <&Geom>::from_robj(&xi).unwrap();
             match &xi.geom {
-                Geometry::LineString(l) => l.densify(md.inner()).into_geom(),
-                Geometry::MultiLineString(l) => l.densify(md.inner()).into_geom(),
-                Geometry::Polygon(p) => p.densify(md.inner()).into_geom(),
-                Geometry::MultiPolygon(p) => p.densify(md.inner()).into_geom(),
+                Geometry::LineString(l) => l.densify(md.inner()).into_geom().into_robj(),
+                Geometry::MultiLineString(l) => l.densify(md.inner()).into_geom().into_robj(),
+                Geometry::Polygon(p) => p.densify(md.inner()).into_geom().into_robj(),
+                Geometry::MultiPolygon(p) => p.densify(md.inner()).into_geom().into_robj(),
                 _ => unreachable!(),
             }
         })
-        .collect::<Vec<Geom>>();
+        .collect::<Vec<Robj>>();
 
     let res = List::from_values(res_vec);
 
@@ -102,24 +130,257 @@ fn densify_haversine(x: List, max_distance: Doubles) -> Robj {
         .into_iter()
         .zip(max_distance.iter())
         .map(|((_, xi), md)| {
+            if xi.is_null() || !md.is_real() {
+                return NULL.into_robj();
+            }
+
             let xi = <&Geom>::from_robj(&xi).unwrap();
             match &xi.geom {
-                Geometry::LineString(l) => l.densify_haversine(md.inner()).into_geom(),
-                Geometry::MultiLineString(l) => l.densify_haversine(md.inner()).into_geom(),
-                Geometry::Polygon(p) => p.densify_haversine(md.inner()).into_geom(),
-                Geometry::MultiPolygon(p) => p.densify_haversine(md.inner()).into_geom(),
+                Geometry::LineString(l) => l.densify_haversine(md.inner()).into_geom().into_robj(),
+                Geometry::MultiLineString(l) => {
+                    l.densify_haversine(md.inner()).into_geom().into_robj()
+                }
+                Geometry::Polygon(p) => p.densify_haversine(md.inner()).into_geom().into_robj(),
+                Geometry::MultiPolygon(p) => {
+                    p.densify_haversine(md.inner()).into_geom().into_robj()
+                }
                 _ => unreachable!(),
             }
         })
-        .collect::<Vec<Geom>>();
+        .collect::<Vec<Robj>>();
 
     let res = List::from_values(res_vec);
 
     as_rsgeo_vctr(res, out_class.as_str())
 }
 
+fn densify_geometry(x: &Geometry, max_distance: f64) -> Geometry {
+    match x {
+        Geometry::LineString(l) => Geometry::LineString(l.densify(max_distance)),
+        Geometry::MultiLineString(l) => Geometry::MultiLineString(l.densify(max_distance)),
+        Geometry::Polygon(p) => Geometry::Polygon(p.densify(max_distance)),
+        Geometry::MultiPolygon(p) => Geometry::MultiPolygon(p.densify(max_distance)),
+        _ => x.clone(),
+    }
+}
+
+// the number of coordinates a geometry would have after densification
+// each segment is split into `ceil(length / max_distance)` pieces
+fn densified_count(lengths: &[f64], n_coords: usize, max_distance: f64) -> usize {
+    lengths.iter().fold(n_coords, |acc, len| {
+        acc + ((len / max_distance).ceil() as usize).saturating_sub(1)
+    })
+}
+
+// Densifies as much as possible without exceeding `max_total` coordinates.
+// The smallest distance within the budget is found by binary search.
+// Returns the geometry and whether the budget prevented full densification.
+fn densify_capped(x: Geometry, max_distance: f64, max_total: usize) -> (Geometry, bool) {
+    // geometries that aren't densified, e.g. Lines, are never capped
+    if !matches!(
+        x,
+        Geometry::LineString(_)
+            | Geometry::MultiLineString(_)
+            | Geometry::Polygon(_)
+            | Geometry::MultiPolygon(_)
+    ) {
+        return (x, false);
+    }
+
+    let lengths = geometry_lines(&x)
+        .iter()
+        .map(|l| l.euclidean_length())
+        .collect::<Vec<f64>>();
+
+    let n_coords = x.coords_count();
+
+    if densified_count(&lengths, n_coords, max_distance) <= max_total {
+        return (densify_geometry(&x, max_distance), false);
+    } else if n_coords >= max_total {
+        return (x, true);
+    }
+
+    // no coordinates are added at the longest segment length
+    let mut lo = max_distance;
+    let mut hi = lengths.iter().fold(max_distance, |acc, len| acc.max(*len));
+
+    for _ in 0..64 {
+        let mid = lo + (hi - lo) / 2.0;
+
+        if densified_count(&lengths, n_coords, mid) <= max_total {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    (densify_geometry(&x, hi), true)
+}
+
+#[extendr]
+fn densify_max_(x: List, max_distance: f64, max_total: Integers) -> List {
+    if !x.inherits("rsgeo") {
+        panic!("`x` must be of class `rsgeo`.");
+    } else if x.inherits("rs_POINT") || x.inherits("rs_MULTIPOINT") {
+        panic!("`x` cannot densify point geometries.")
+    }
+
+    if max_distance.is_nan() || max_distance <= 0.0 {
+        panic!("`max_distance` must be a positive number")
+    }
+
+    let out_class = rsgeo_type(&x);
+    let n_x = x.len();
+    let n_mt = max_total.len();
+
+    let max_total = if n_mt == 1 {
+        Integers::from_values(vec![max_total[0]; n_x])
+    } else if n_mt != n_x {
+        panic!("`max_total` must be the same length as `x` or length 1")
+    } else {
+        max_total
+    };
+
+    let max_total = max_total
+        .iter()
+        .map(|mi| {
+            if mi.is_na() {
+                None
+            } else {
+                Some(mi.inner().max(0) as usize)
+            }
+        })
+        .collect::<Vec<Option<usize>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(max_total.into_par_iter())
+        .map(|(xi, mi)| {
+            let xi = xi?;
+
+            match mi {
+                Some(mi) => Some(densify_capped(xi, max_distance, mi)),
+                None => Some((densify_geometry(&xi, max_distance), false)),
+            }
+        })
+        .collect::<Vec<Option<(Geometry, bool)>>>();
+
+    let (geoms, capped): (Vec<Robj>, Vec<bool>) = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some((g, is_capped)) => (Geom::from(g).into_robj(), is_capped),
+            None => (NULL.into_robj(), false),
+        })
+        .unzip();
+
+    List::from_names_and_values(
+        ["geometry", "capped"],
+        [
+            as_rsgeo_vctr(List::from_values(geoms), out_class.as_str()),
+            Logicals::from_values(capped).into_robj(),
+        ],
+    )
+    .unwrap()
+}
+
+// Splits segments longer than `min_segment` into `ceil(length / max_distance)`
+// pieces of equal length. Shorter segments are kept as they are.
+fn densify_coords_threshold(x: &LineString, min_segment: f64, max_distance: f64) -> LineString {
+    let mut res: Vec<Coord> = Vec::with_capacity(x.0.len());
+
+    for line in x.lines() {
+        res.push(line.start);
+
+        let len = line.euclidean_length();
+
+        if len > min_segment {
+            let n = (len / max_distance).ceil() as usize;
+
+            for i in 1..n {
+                res.push(line.start + line.delta() * (i as f64 / n as f64));
+            }
+        }
+    }
+
+    if let Some(last) = x.0.last() {
+        res.push(*last);
+    }
+
+    LineString::new(res)
+}
+
+fn densify_polygon_threshold(x: &Polygon, min_segment: f64, max_distance: f64) -> Polygon {
+    Polygon::new(
+        densify_coords_threshold(x.exterior(), min_segment, max_distance),
+        x.interiors()
+            .iter()
+            .map(|r| densify_coords_threshold(r, min_segment, max_distance))
+            .collect(),
+    )
+}
+
+#[extendr]
+/// @param min_segment segments with a length less than or equal to
+///   `min_segment` are not densified.
+/// @export
+/// @rdname densify
+fn densify_threshold(x: List, min_segment: f64, max_distance: f64) -> Robj {
+    if !x.inherits("rsgeo") {
+        panic!("`x` must be of class `rsgeo`.");
+    } else if x.inherits("rs_POINT") || x.inherits("rs_MULTIPOINT") {
+        panic!("`x` cannot densify point geometries.")
+    }
+
+    if min_segment.is_nan() || min_segment < 0.0 {
+        panic!("`min_segment` must be a non-negative number")
+    } else if max_distance.is_nan() || max_distance <= 0.0 {
+        panic!("`max_distance` must be a positive number")
+    }
+
+    let out_class = rsgeo_type(&x);
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let res: Geometry = match xi? {
+                Geometry::LineString(l) => {
+                    densify_coords_threshold(&l, min_segment, max_distance).into()
+                }
+                Geometry::MultiLineString(ml) => ml
+                    .iter()
+                    .map(|l| densify_coords_threshold(l, min_segment, max_distance))
+                    .collect::<MultiLineString>()
+                    .into(),
+                Geometry::Polygon(p) => {
+                    densify_polygon_threshold(&p, min_segment, max_distance).into()
+                }
+                Geometry::MultiPolygon(mp) => mp
+                    .iter()
+                    .map(|p| densify_polygon_threshold(p, min_segment, max_distance))
+                    .collect::<MultiPolygon>()
+                    .into(),
+                g => g,
+            };
+
+            Some(res)
+        })
+        .collect::<Vec<Option<Geometry>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), out_class.as_str())
+}
+
 extendr_module! {
     mod densify;
     fn densify_euclidean;
     fn densify_haversine;
+    fn densify_max_;
+    fn densify_threshold;
 }
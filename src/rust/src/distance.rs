@@ -1,10 +1,17 @@
 use extendr_api::prelude::*;
-use geo_types::{Geometry, Point};
+use geo_types::{Geometry, Point, Rect};
 use rayon::prelude::*;
-use sfconversions::Geom; // for parallel processing
+use sfconversions::{geometry_from_list, Geom};
+
+use crate::geodesic::{as_geodesic, geodesic_distance};
+use crate::spatial_index::create_cached_rtree;
+use crate::utils::new_data_frame;
+use geographiclib_rs::Geodesic;
+use rstar::{Point as RPoint, RTreeObject, AABB};
 
 use geo::{
-    EuclideanDistance, GeodesicDistance, HausdorffDistance, HaversineDistance, VincentyDistance,
+    BoundingRect, Centroid, EuclideanDistance, HausdorffDistance, HaversineDistance,
+    VincentyDistance,
 };
 
 #[extendr]
@@ -30,6 +37,10 @@ use geo::{
 /// * Haversine, Geodesic, and Vicenty distances only work with `rs_POINT` geometries.
 /// @param x and object of class `rsgeo`
 /// @param y and object of class `rsgeo`
+/// @param ellipsoid the name of an ellipsoid or a numeric vector of its
+///   semi-major axis in meters and flattening. One of `"WGS84"`, `"GRS80"`,
+///   `"sphere"`, `"moon"`, or `"mars"`. Defaults to `"WGS84"`. Only used by
+///   the geodesic distance functions.
 /// @export
 /// @rdname distance
 /// @examples
@@ -130,7 +141,7 @@ fn distance_vicenty_pairwise(x: List, y: List) -> Doubles {
 #[extendr]
 /// @export
 /// @rdname distance
-fn distance_geodesic_pairwise(x: List, y: List) -> Doubles {
+fn distance_geodesic_pairwise(x: List, y: List, #[default = "'WGS84'"] ellipsoid: Robj) -> Doubles {
     let x_is_point = x.inherits("rs_POINT");
     let y_is_point = x.inherits("rs_POINT");
 
@@ -138,6 +149,8 @@ fn distance_geodesic_pairwise(x: List, y: List) -> Doubles {
         panic!("`x` and `y` must be `rs_POINT` geometries")
     }
 
+    let g = as_geodesic(&ellipsoid);
+
     x.iter()
         .zip(y.iter())
         .map(|((_, xi), (_, yi))| {
@@ -147,7 +160,7 @@ fn distance_geodesic_pairwise(x: List, y: List) -> Doubles {
                 let xg = Point::from(Geom::from(xi));
                 let yg = Point::from(Geom::from(yi));
 
-                Rfloat::from(xg.geodesic_distance(&yg))
+                Rfloat::from(geodesic_distance(&g, &xg, &yg))
             }
         })
         .collect::<Doubles>()
@@ -192,6 +205,15 @@ extendr_module! {
     fn distance_vicenty_matrix;
     fn distance_geodesic_matrix;
     fn distance_haversine_matrix;
+    fn dwithin_pairwise;
+    fn dwithin_haversine_pairwise;
+    fn dwithin_matrix;
+    fn dwithin_haversine_matrix;
+    fn knn_;
+    fn point_line_distance;
+    fn point_line_distance_matrix;
+    fn centroid_distance_;
+    fn centroid_distance_matrix_;
 }
 
 // TODO check if x and y are identical then only calculate
@@ -400,11 +422,13 @@ fn distance_vicenty_matrix(x: List, y: List) -> Robj {
 #[extendr]
 /// @export
 /// @rdname distance
-fn distance_geodesic_matrix(x: List, y: List) -> Robj {
+fn distance_geodesic_matrix(x: List, y: List, #[default = "'WGS84'"] ellipsoid: Robj) -> Robj {
     if !x.inherits("rs_POINT") || !y.inherits("rs_POINT") {
         panic!("`x` and `y` must both be `rs_POINT` geometries")
     }
 
+    let g = as_geodesic(&ellipsoid);
+
     let n_x = x.len();
     let n_y = y.len();
 
@@ -430,7 +454,7 @@ fn distance_geodesic_matrix(x: List, y: List) -> Robj {
             Some(yi) => x
                 .iter()
                 .map(|xi| match xi {
-                    Some(xi) => Some(yi.geodesic_distance(xi)),
+                    Some(xi) => Some(geodesic_distance(&g, &yi, xi)),
                     None => None,
                 })
                 .collect::<Vec<Option<f64>>>(),
@@ -445,3 +469,440 @@ fn distance_geodesic_matrix(x: List, y: List) -> Robj {
         .set_attrib("dim", [n_y, n_x])
         .unwrap()
 }
+
+// the smallest distance between two bounding rectangles
+fn rect_distance(a: &Rect, b: &Rect) -> f64 {
+    let dx = (b.min().x - a.max().x).max(a.min().x - b.max().x).max(0.0);
+    let dy = (b.min().y - a.max().y).max(a.min().y - b.max().y).max(0.0);
+    dx.hypot(dy)
+}
+
+// Bounding rectangles are compared first so that the distance between the
+// geometries is only calculated when they could be within `distance`.
+fn is_within_distance(x: &Geometry, y: &Geometry, distance: f64) -> bool {
+    if let (Some(bx), Some(by)) = (x.bounding_rect(), y.bounding_rect()) {
+        if rect_distance(&bx, &by) > distance {
+            return false;
+        }
+    }
+
+    x.euclidean_distance(y) <= distance
+}
+
+fn is_within_haversine(x: &Geometry, y: &Geometry, distance: f64) -> bool {
+    let xp = Point::try_from(x.clone()).unwrap();
+    let yp = Point::try_from(y.clone()).unwrap();
+    xp.haversine_distance(&yp) <= distance
+}
+
+fn check_distance(distance: f64) {
+    if distance.is_nan() || distance < 0.0 {
+        panic!("`distance` must be a non-negative number")
+    }
+}
+
+// pairs the geometries of `x` and `y` recycling either if it is length 1
+pub fn paired_geoms(x: List, y: List) -> Vec<(Option<Geometry>, Option<Geometry>)> {
+    let n_x = x.len();
+    let n_y = y.len();
+
+    if n_x != n_y && n_x != 1 && n_y != 1 {
+        panic!("`x` and `y` must be the same length or length 1")
+    }
+
+    let n = n_x.max(n_y);
+
+    geometry_from_list(x)
+        .into_iter()
+        .cycle()
+        .take(n)
+        .zip(geometry_from_list(y).into_iter().cycle().take(n))
+        .collect()
+}
+
+fn dwithin_pairwise_impl<F>(x: List, y: List, distance: f64, f: F) -> Logicals
+where
+    F: Fn(&Geometry, &Geometry, f64) -> bool + Sync,
+{
+    check_distance(distance);
+
+    let res_vec = paired_geoms(x, y)
+        .into_par_iter()
+        .map(|(xi, yi)| match (xi, yi) {
+            (Some(xi), Some(yi)) => Rbool::from(f(&xi, &yi, distance)),
+            _ => Rbool::na(),
+        })
+        .collect::<Vec<Rbool>>();
+
+    Logicals::from_values(res_vec)
+}
+
+fn dwithin_matrix_impl<F>(x: List, y: List, distance: f64, f: F) -> Robj
+where
+    F: Fn(&Geometry, &Geometry, f64) -> bool + Sync,
+{
+    check_distance(distance);
+
+    let n_x = x.len();
+    let n_y = y.len();
+
+    let x = geometry_from_list(x);
+    let y = geometry_from_list(y);
+    let f = &f;
+
+    // filled column by column where each column is a geometry of `y`
+    let res_vec = y
+        .par_iter()
+        .flat_map_iter(|yi| {
+            x.iter().map(move |xi| match (xi, yi) {
+                (Some(xi), Some(yi)) => Rbool::from(f(xi, yi, distance)),
+                _ => Rbool::na(),
+            })
+        })
+        .collect::<Vec<Rbool>>();
+
+    Logicals::from_values(res_vec)
+        .into_robj()
+        .set_class(["matrix", "array"])
+        .unwrap()
+        .set_attrib("dim", [n_x, n_y])
+        .unwrap()
+}
+
+#[extendr]
+/// Identify Geometries Within a Distance
+///
+/// Tests whether geometries are within a distance of each other. This is the
+/// predicate behind queries such as "find everything within 500 meters" and
+/// is more efficient than calculating distances and comparing them in R.
+///
+/// @param x an object of class `rsgeo`
+/// @param y an object of class `rsgeo`
+/// @param distance a non-negative scalar numeric. A pair of geometries is
+///   within the distance when the distance between them is less than or equal
+///   to `distance`.
+///
+/// @details
+///
+/// `dwithin_pairwise()` and `dwithin_matrix()` use Euclidean distance in the
+/// units of the geometries. Bounding boxes are compared first so the distance
+/// is only calculated for geometries that could be within `distance`.
+///
+/// The `_haversine` variants only work with `rs_POINT` geometries and
+/// `distance` is in meters. See [`distance_haversine_pairwise()`].
+///
+/// For the `_pairwise()` functions, `x` and `y` must be the same length or
+/// either must be length 1.
+///
+/// @export
+/// @rdname dwithin
+/// @returns
+/// `_pairwise()` functions return a logical vector and `_matrix()` functions
+/// return a logical matrix with a row for each geometry in `x` and a column
+/// for each geometry in `y`. Missing geometries result in `NA`.
+/// @examples
+/// x <- geom_point(c(0, 3, 10), c(0, 4, 0))
+/// y <- geom_point(0, 0)
+/// dwithin_pairwise(x, y, 5)
+/// dwithin_matrix(x, x, 5)
+///
+/// pnts <- geom_point(c(-0.1, -0.1), c(51.5, 51.6))
+/// dwithin_haversine_pairwise(pnts[1], pnts[2], 10000)
+fn dwithin_pairwise(x: List, y: List, distance: f64) -> Logicals {
+    if !x.inherits("rsgeo") || !y.inherits("rsgeo") {
+        panic!("`x` and `y` must be rsgeo geometries")
+    }
+
+    dwithin_pairwise_impl(x, y, distance, is_within_distance)
+}
+
+#[extendr]
+/// @export
+/// @rdname dwithin
+fn dwithin_haversine_pairwise(x: List, y: List, distance: f64) -> Logicals {
+    if !x.inherits("rs_POINT") || !y.inherits("rs_POINT") {
+        panic!("`x` and `y` must be `rs_POINT` geometries")
+    }
+
+    dwithin_pairwise_impl(x, y, distance, is_within_haversine)
+}
+
+#[extendr]
+/// @export
+/// @rdname dwithin
+fn dwithin_matrix(x: List, y: List, distance: f64) -> Robj {
+    if !x.inherits("rsgeo") || !y.inherits("rsgeo") {
+        panic!("`x` and `y` must be rsgeo geometries")
+    }
+
+    dwithin_matrix_impl(x, y, distance, is_within_distance)
+}
+
+#[extendr]
+/// @export
+/// @rdname dwithin
+fn dwithin_haversine_matrix(x: List, y: List, distance: f64) -> Robj {
+    if !x.inherits("rs_POINT") || !y.inherits("rs_POINT") {
+        panic!("`x` and `y` must be `rs_POINT` geometries")
+    }
+
+    dwithin_matrix_impl(x, y, distance, is_within_haversine)
+}
+
+// grows an envelope by `r` in every direction
+pub fn expand_envelope<P: RPoint<Scalar = f64>>(x: &AABB<P>, r: f64) -> AABB<P> {
+    let (lower, upper) = (x.lower(), x.upper());
+    AABB::from_corners(
+        P::generate(|i| lower.nth(i) - r),
+        P::generate(|i| upper.nth(i) + r),
+    )
+}
+
+pub fn envelope_diagonal<P: RPoint<Scalar = f64>>(x: &AABB<P>) -> f64 {
+    let (lower, upper) = (x.lower(), x.upper());
+    (upper.nth(0) - lower.nth(0)).hypot(upper.nth(1) - lower.nth(1))
+}
+
+#[extendr]
+fn knn_(x: List, y: List, k: i32) -> Robj {
+    if !x.inherits("rsgeo") || !y.inherits("rsgeo") {
+        panic!("`x` and `y` must be rsgeo geometries")
+    }
+
+    if k < 1 {
+        panic!("`k` must be a positive integer")
+    }
+
+    let k = k as usize;
+    let tree = create_cached_rtree(y);
+    let n_tree = tree.size();
+
+    // the search radius starts at roughly the spacing between k geometries
+    // if they were spread evenly over the extent of `y`
+    let step = envelope_diagonal(&tree.root().envelope()) * (k as f64 / n_tree as f64).sqrt();
+    let step = if step > 0.0 { step } else { 1.0 };
+
+    // Geometries within `r` of a geometry must intersect its envelope grown by
+    // `r`. The radius is doubled until at least `k` geometries are within it
+    // so that the k nearest are guaranteed to be among the candidates.
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            // empty geometries have no envelope to search from
+            xi.as_ref()?.bounding_rect()?;
+
+            let xi = Geom::from(xi?);
+            let env = xi.envelope();
+            let mut r = step;
+
+            loop {
+                let cands = tree
+                    .locate_in_envelope_intersecting(&expand_envelope(&env, r))
+                    .collect::<Vec<_>>();
+
+                let all_found = cands.len() == n_tree;
+
+                let mut nearest = cands
+                    .into_iter()
+                    .map(|cnd| (cnd.geom().geom.euclidean_distance(&xi.geom), cnd.data))
+                    .filter(|(d, _)| all_found || *d <= r)
+                    .collect::<Vec<(f64, usize)>>();
+
+                if nearest.len() >= k || all_found {
+                    nearest.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+                    nearest.truncate(k);
+                    return Some(nearest);
+                }
+
+                r *= 2.0;
+            }
+        })
+        .collect::<Vec<Option<Vec<(f64, usize)>>>>();
+
+    let mut from: Vec<i32> = Vec::new();
+    let mut to: Vec<i32> = Vec::new();
+    let mut rank: Vec<i32> = Vec::new();
+    let mut distance: Vec<f64> = Vec::new();
+
+    for (i, nearest) in res_vec.into_iter().enumerate() {
+        for (j, (d, idx)) in nearest.unwrap_or_default().into_iter().enumerate() {
+            from.push(i as i32 + 1);
+            to.push(idx as i32 + 1);
+            rank.push(j as i32 + 1);
+            distance.push(d);
+        }
+    }
+
+    let n = from.len();
+
+    new_data_frame(
+        ["from", "to", "rank", "distance"],
+        [
+            Integers::from_values(from).into_robj(),
+            Integers::from_values(to).into_robj(),
+            Integers::from_values(rank).into_robj(),
+            Doubles::from_values(distance).into_robj(),
+        ],
+        n,
+    )
+}
+
+fn check_point_line(points: &List, lines: &List) {
+    if !points.inherits("rs_POINT") {
+        panic!("`points` must be an `rs_POINT`")
+    } else if !(lines.inherits("rs_LINESTRING") || lines.inherits("rs_MULTILINESTRING")) {
+        panic!("`lines` must be an `rs_LINESTRING` or `rs_MULTILINESTRING`")
+    }
+}
+
+#[extendr]
+/// Distance from Points to Lines
+///
+/// Calculates the shortest Euclidean distance from points to LineStrings.
+/// This is the perpendicular distance to the line when the closest location
+/// is between its vertices. It is commonly used to check how far observations,
+/// such as GPS fixes, are from the route they are referenced to.
+///
+/// @param points an object of class `rs_POINT`
+/// @param lines an object of class `rs_LINESTRING` or `rs_MULTILINESTRING`
+///
+/// @details
+///
+/// `point_line_distance()` calculates the distance between each point and the
+/// corresponding line. `points` and `lines` must be the same length or
+/// either must be length 1. `point_line_distance_matrix()` calculates the
+/// distance between every point and every line.
+///
+/// Missing geometries result in `NA`.
+///
+/// @export
+/// @rdname point_line_distance
+/// @returns
+/// `point_line_distance()` returns a numeric vector. `point_line_distance_matrix()`
+/// returns a numeric matrix with a row for each point and a column for each
+/// line.
+/// @examples
+/// lns <- geom_linestring(c(0, 10, 0, 0), c(0, 0, 0, 10), id = c(1, 1, 2, 2))
+/// pnts <- geom_point(c(5, 3), c(2, 4))
+/// point_line_distance(pnts, lns)
+/// point_line_distance_matrix(pnts, lns)
+fn point_line_distance(points: List, lines: List) -> Doubles {
+    check_point_line(&points, &lines);
+
+    let (n_p, n_l) = (points.len(), lines.len());
+
+    if n_p != n_l && n_p != 1 && n_l != 1 {
+        panic!("`points` and `lines` must be the same length or length 1")
+    }
+
+    let res_vec = paired_geoms(points, lines)
+        .into_par_iter()
+        .map(|(pi, li)| Some(pi?.euclidean_distance(&li?)))
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res_vec)
+}
+
+#[extendr]
+/// @export
+/// @rdname point_line_distance
+fn point_line_distance_matrix(points: List, lines: List) -> Robj {
+    check_point_line(&points, &lines);
+
+    let (n_p, n_l) = (points.len(), lines.len());
+    let points = geometry_from_list(points);
+    let lines = geometry_from_list(lines);
+
+    // filled column by column where each column is a line
+    let res_vec = lines
+        .par_iter()
+        .flat_map_iter(|li| {
+            points.iter().map(move |pi| match (pi, li) {
+                (Some(pi), Some(li)) => Some(pi.euclidean_distance(li)),
+                _ => None,
+            })
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res_vec)
+        .into_robj()
+        .set_class(["matrix", "array"])
+        .unwrap()
+        .set_attrib("dim", [n_p, n_l])
+        .unwrap()
+}
+
+fn centroids_from_list(x: List) -> Vec<Option<Point>> {
+    geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| xi?.centroid())
+        .collect()
+}
+
+fn point_distance(x: &Point, y: &Point, metric: &str, g: &Geodesic) -> f64 {
+    match metric {
+        "planar" => x.euclidean_distance(y),
+        "haversine" => x.haversine_distance(y),
+        _ => geodesic_distance(g, x, y),
+    }
+}
+
+// checks the `metric` argument shared by functions that measure planar,
+// haversine, or geodesic distances, lengths, or areas
+pub fn check_metric(metric: &str) {
+    if !["planar", "haversine", "geodesic"].contains(&metric) {
+        panic!("`metric` must be one of `planar`, `haversine`, or `geodesic`")
+    }
+}
+
+#[extendr]
+fn centroid_distance_(x: List, y: List, metric: &str, ellipsoid: Robj) -> Doubles {
+    if !x.inherits("rsgeo") || !y.inherits("rsgeo") {
+        panic!("`x` and `y` must be rsgeo geometries")
+    }
+
+    check_metric(metric);
+    let g = as_geodesic(&ellipsoid);
+
+    let res_vec = paired_geoms(x, y)
+        .into_par_iter()
+        .map(|(xi, yi)| {
+            let (xc, yc) = (xi?.centroid()?, yi?.centroid()?);
+            Some(point_distance(&xc, &yc, metric, &g))
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res_vec)
+}
+
+#[extendr]
+fn centroid_distance_matrix_(x: List, y: List, metric: &str, ellipsoid: Robj) -> Robj {
+    if !x.inherits("rsgeo") || !y.inherits("rsgeo") {
+        panic!("`x` and `y` must be rsgeo geometries")
+    }
+
+    check_metric(metric);
+    let g = &as_geodesic(&ellipsoid);
+
+    let (n_x, n_y) = (x.len(), y.len());
+    let x = centroids_from_list(x);
+    let y = centroids_from_list(y);
+
+    // filled column by column where each column is a geometry of `y`
+    let res_vec = y
+        .par_iter()
+        .flat_map_iter(|yi| {
+            x.iter().map(move |xi| match (xi, yi) {
+                (Some(xi), Some(yi)) => Some(point_distance(xi, yi, metric, g)),
+                _ => None,
+            })
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res_vec)
+        .into_robj()
+        .set_class(["matrix", "array"])
+        .unwrap()
+        .set_attrib("dim", [n_x, n_y])
+        .unwrap()
+}
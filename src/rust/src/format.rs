@@ -0,0 +1,233 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::verify_rsgeo};
+
+use geo::{BoundingRect, CoordsIter, MapCoords};
+use geo_types::{coord, Coord, Geometry};
+
+use crate::similarity::almost_equals;
+use crate::utils::with_threads;
+use rayon::prelude::*;
+use wkb::{geom_to_wkb, wkb_to_geom};
+use wkt::{ToWkt, TryFromWkt};
+
+// Rounds to `precision` decimal places. Formatting and parsing the number
+// gives the closest decimal exactly rather than scaling by a power of 10.
+// Adding 0 turns -0 into 0.
+fn round_num(x: f64, precision: usize) -> f64 {
+    format!("{:.*}", precision, x).parse::<f64>().unwrap() + 0.0
+}
+
+fn round_coords(x: &Geometry, precision: usize) -> Geometry {
+    x.map_coords(|c| coord! { x: round_num(c.x, precision), y: round_num(c.y, precision) })
+}
+
+fn fmt_num(x: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(p) => round_num(x, p).to_string(),
+        None => x.to_string(),
+    }
+}
+
+fn fmt_coord(x: &Coord, precision: Option<usize>) -> String {
+    format!("{} {}", fmt_num(x.x, precision), fmt_num(x.y, precision))
+}
+
+fn geometry_type(x: &Geometry) -> &'static str {
+    match x {
+        Geometry::Point(_) => "POINT",
+        Geometry::MultiPoint(_) => "MULTIPOINT",
+        Geometry::Line(_) | Geometry::LineString(_) => "LINESTRING",
+        Geometry::MultiLineString(_) => "MULTILINESTRING",
+        Geometry::Polygon(_) | Geometry::Rect(_) | Geometry::Triangle(_) => "POLYGON",
+        Geometry::MultiPolygon(_) => "MULTIPOLYGON",
+        Geometry::GeometryCollection(_) => "GEOMETRYCOLLECTION",
+    }
+}
+
+pub fn fmt_wkt(x: &Geometry, precision: Option<usize>) -> String {
+    let is_empty = match x {
+        Geometry::MultiPoint(mp) => mp.0.is_empty(),
+        Geometry::LineString(l) => l.0.is_empty(),
+        Geometry::MultiLineString(ml) => ml.0.is_empty(),
+        Geometry::Polygon(p) => p.exterior().0.is_empty(),
+        Geometry::MultiPolygon(mp) => mp.0.is_empty(),
+        Geometry::GeometryCollection(gc) => gc.0.is_empty(),
+        _ => false,
+    };
+
+    if is_empty {
+        return format!("{} EMPTY", geometry_type(x));
+    }
+
+    // coordinates are rounded before writing so that only the output is
+    // affected and trailing zeros are dropped
+    match precision {
+        Some(p) => round_coords(x, p).wkt_string(),
+        None => x.wkt_string(),
+    }
+}
+
+// geometries with more than `max_coords` coordinates are summarized by
+// their number of points and bounding box rather than printed in full
+fn fmt_summary(x: &Geometry, max_coords: usize, precision: Option<usize>) -> String {
+    let n = x.coords_count();
+
+    if n <= max_coords {
+        return fmt_wkt(x, precision);
+    }
+
+    match x.bounding_rect() {
+        Some(bbox) => format!(
+            "{} ({} points, bbox ({}, {}))",
+            geometry_type(x),
+            n,
+            fmt_coord(&bbox.min(), precision),
+            fmt_coord(&bbox.max(), precision)
+        ),
+        None => format!("{} ({} points)", geometry_type(x), n),
+    }
+}
+
+// negative values indicate that coordinates should not be rounded
+fn as_precision(precision: i32) -> Option<usize> {
+    if precision < 0 {
+        None
+    } else {
+        Some(precision as usize)
+    }
+}
+
+#[extendr]
+fn format_geom_(x: List, max_coords: i32, precision: i32) -> Strings {
+    verify_rsgeo(&x);
+
+    if max_coords < 0 {
+        panic!("`max_coords` must be a positive integer")
+    }
+
+    let max_coords = max_coords as usize;
+    let precision = as_precision(precision);
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| xi.map(|g| fmt_summary(&g, max_coords, precision)))
+        .collect::<Vec<Option<String>>>();
+
+    strings_from_options(res_vec)
+}
+
+#[extendr]
+fn to_wkt_(x: List, precision: i32, n_threads: i32) -> Strings {
+    verify_rsgeo(&x);
+
+    let precision = as_precision(precision);
+    let geoms = geometry_from_list(x);
+
+    let res_vec = with_threads(n_threads, || {
+        geoms
+            .into_par_iter()
+            .map(|xi| xi.map(|g| fmt_wkt(&g, precision)))
+            .collect::<Vec<Option<String>>>()
+    });
+
+    strings_from_options(res_vec)
+}
+
+#[extendr]
+fn to_wkb_(x: List, n_threads: i32) -> List {
+    verify_rsgeo(&x);
+
+    let geoms = geometry_from_list(x);
+
+    // geometries that can't be written are treated as missing
+    let res_vec = with_threads(n_threads, || {
+        geoms
+            .into_par_iter()
+            .map(|xi| geom_to_wkb(&xi?).ok())
+            .collect::<Vec<Option<Vec<u8>>>>()
+    });
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(bytes) => Raw::from_bytes(&bytes).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    List::from_values(res)
+}
+
+fn strings_from_options(x: Vec<Option<String>>) -> Strings {
+    let res = x
+        .into_iter()
+        .map(|xi| match xi {
+            Some(s) => Rstr::from(s),
+            None => Rstr::na(),
+        })
+        .collect::<Vec<Rstr>>();
+
+    Strings::from_values(res)
+}
+
+// Writes a geometry as `format` and reads it back. Geometries that can't be
+// written or parsed are `None`.
+fn roundtrip(x: &Geometry, format: &str, precision: Option<usize>) -> Option<Geometry> {
+    if format == "wkt" {
+        return Geometry::try_from_wkt_str(&fmt_wkt(x, precision)).ok();
+    }
+
+    let bytes = match precision {
+        Some(p) => geom_to_wkb(&round_coords(x, p)),
+        None => geom_to_wkb(x),
+    }
+    .ok()?;
+
+    wkb_to_geom(&mut bytes.as_slice()).ok()
+}
+
+#[extendr]
+fn roundtrip_check_(x: List, format: &str, precision: i32, tolerance: f64) -> Logicals {
+    verify_rsgeo(&x);
+
+    if format != "wkt" && format != "wkb" {
+        panic!("`format` must be one of `wkt` or `wkb`")
+    }
+
+    if tolerance.is_nan() || tolerance < 0.0 {
+        panic!("`tolerance` must be a non-negative number")
+    }
+
+    let precision = as_precision(precision);
+
+    // geometries that fail to parse don't round trip
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let g = xi?;
+
+            Some(match roundtrip(&g, format, precision) {
+                Some(p) => !almost_equals(&g, &p, tolerance),
+                None => true,
+            })
+        })
+        .collect::<Vec<Option<bool>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(failed) => Rbool::from(failed),
+            None => Rbool::na(),
+        })
+        .collect::<Vec<Rbool>>();
+
+    Logicals::from_values(res)
+}
+
+extendr_module! {
+    mod format;
+    fn format_geom_;
+    fn to_wkt_;
+    fn to_wkb_;
+    fn roundtrip_check_;
+}
@@ -0,0 +1,197 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo_types::{coord, Coord, Geometry, LineString, Point, Polygon};
+use geographiclib_rs::{DirectGeodesic, Geodesic, InverseGeodesic, PolygonArea, Winding};
+
+use crate::construction::IsReal;
+use rayon::prelude::*;
+
+// named ellipsoids with their semi-major axis in meters and flattening
+const ELLIPSOIDS: [(&str, f64, f64); 5] = [
+    ("WGS84", 6378137.0, 1.0 / 298.257223563),
+    ("GRS80", 6378137.0, 1.0 / 298.257222101),
+    ("sphere", 6371008.8, 0.0),
+    ("moon", 1737400.0, 0.0),
+    ("mars", 3396190.0, 1.0 / 169.894447223612),
+];
+
+// Creates the geodesic calculator for an `ellipsoid` argument. It is either
+// the name of an ellipsoid or a numeric vector of the semi-major axis and
+// flattening.
+pub fn as_geodesic(ellipsoid: &Robj) -> Geodesic {
+    if let Some(name) = ellipsoid.as_str() {
+        return match ELLIPSOIDS
+            .iter()
+            .find(|(n, _, _)| n.eq_ignore_ascii_case(name))
+        {
+            Some(&(_, a, f)) => Geodesic::new(a, f),
+            None => panic!("unknown `ellipsoid` \"{name}\""),
+        };
+    }
+
+    match ellipsoid.as_real_slice() {
+        Some(&[a, f]) if a.is_finite() && a > 0.0 && f.is_finite() && f < 1.0 => {
+            Geodesic::new(a, f)
+        }
+        _ => panic!(
+            "`ellipsoid` must be the name of an ellipsoid or a numeric vector of the semi-major axis and flattening"
+        ),
+    }
+}
+
+pub fn geodesic_length(g: &Geodesic, x: &LineString) -> f64 {
+    x.lines()
+        .map(|l| -> f64 { g.inverse(l.start.y, l.start.x, l.end.y, l.end.x) })
+        .sum()
+}
+
+// the signed area of a ring. Counter-clockwise rings are positive.
+fn ring_area(g: &Geodesic, x: &LineString) -> f64 {
+    let mut pa = PolygonArea::new(g, Winding::CounterClockwise);
+    let n = if x.is_closed() {
+        x.0.len().saturating_sub(1)
+    } else {
+        x.0.len()
+    };
+
+    x.0.iter().take(n).for_each(|c| pa.add_point(c.y, c.x));
+
+    let (_, area, _) = pa.compute(true);
+    area
+}
+
+// Holes are removed from the area of the exterior and the sign of the
+// exterior is kept.
+fn polygon_area(g: &Geodesic, x: &Polygon) -> f64 {
+    if x.exterior().0.is_empty() {
+        return 0.0;
+    }
+
+    let exterior = ring_area(g, x.exterior());
+    let holes = x
+        .interiors()
+        .iter()
+        .map(|h| ring_area(g, h).abs())
+        .sum::<f64>();
+
+    exterior - holes * exterior.signum()
+}
+
+pub fn geodesic_area_signed(g: &Geodesic, x: &Geometry) -> f64 {
+    match x {
+        Geometry::Polygon(p) => polygon_area(g, p),
+        Geometry::MultiPolygon(mp) => mp.iter().map(|p| polygon_area(g, p)).sum(),
+        Geometry::Rect(r) => polygon_area(g, &r.to_polygon()),
+        Geometry::Triangle(t) => polygon_area(g, &t.to_polygon()),
+        Geometry::GeometryCollection(gc) => gc.iter().map(|gi| geodesic_area_signed(g, gi)).sum(),
+        _ => 0.0,
+    }
+}
+
+pub fn geodesic_area_unsigned(g: &Geodesic, x: &Geometry) -> f64 {
+    match x {
+        Geometry::MultiPolygon(mp) => mp.iter().map(|p| polygon_area(g, p).abs()).sum(),
+        Geometry::GeometryCollection(gc) => gc.iter().map(|gi| geodesic_area_unsigned(g, gi)).sum(),
+        x => geodesic_area_signed(g, x).abs(),
+    }
+}
+
+pub fn geodesic_distance(g: &Geodesic, x: &Point, y: &Point) -> f64 {
+    g.inverse(x.y(), x.x(), y.y(), y.x())
+}
+
+fn geodesic_destination_coord(g: &Geodesic, x: Coord, bearing: f64, distance: f64) -> Coord {
+    let (lat, lon): (f64, f64) = g.direct(x.y, x.x, bearing, distance);
+    coord! { x: lon, y: lat }
+}
+
+#[extendr]
+/// Identify a Geodesic Destination Point
+///
+/// Given a vector of point geometries, bearings, and distances, identify a
+/// destination location along the geodesic of an ellipsoid. Unlike
+/// [`haversine_destination()`] this accounts for the flattening of the
+/// ellipsoid.
+///
+/// @param x an object of class `rs_POINT`
+/// @param bearing a numeric vector specifying the degree of the direction where 0 is north
+/// @param distance a numeric vector specifying the distance to travel in the direction specified by `bearing` in meters
+/// @param ellipsoid the name of an ellipsoid or a numeric vector of its
+///   semi-major axis in meters and flattening. One of `"WGS84"`, `"GRS80"`,
+///   `"sphere"`, `"moon"`, or `"mars"`. Defaults to `"WGS84"`.
+///
+/// @details
+///
+/// `"sphere"` is a sphere with the mean radius of the earth, 6371008.8 m.
+/// `"moon"` and `"mars"` use the IAU reference radii. A flattening of 0
+/// is a sphere.
+///
+/// @export
+/// @returns an object of class `rs_POINT`
+/// @examples
+/// pnts <- geom_point(c(0, 10), c(0, 45))
+/// geodesic_destination(pnts, 90, 100000)
+/// geodesic_destination(pnts, 90, 100000, ellipsoid = "GRS80")
+///
+/// # the semi-major axis and flattening of the ellipsoid
+/// geodesic_destination(pnts, 90, 100000, ellipsoid = c(6378388, 1 / 297))
+fn geodesic_destination(
+    x: List,
+    bearing: Doubles,
+    distance: Doubles,
+    #[default = "'WGS84'"] ellipsoid: Robj,
+) -> Robj {
+    if !x.inherits("rs_POINT") {
+        panic!("`x` must be of class `rs_POINT`")
+    }
+
+    let g = as_geodesic(&ellipsoid);
+
+    let n = x.len();
+    let n_b = bearing.len();
+    let n_d = distance.len();
+
+    if (n_b != n) && (n_b != 1) {
+        panic!("`bearing` must be the same length as `x` or length 1")
+    } else if (n_d != n) && (n_d != 1) {
+        panic!("`distance` must be the same length as `x` or length 1")
+    }
+
+    let params = (0..n)
+        .map(|i| {
+            let b = if n_b == 1 { bearing[0] } else { bearing[i] };
+            let d = if n_d == 1 { distance[0] } else { distance[i] };
+
+            if b.is_real() && d.is_real() {
+                Some((b.inner(), d.inner()))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<Option<(f64, f64)>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(params)
+        .map(|(xi, pi)| match (xi?, pi?) {
+            (Geometry::Point(p), (b, d)) => Some(geodesic_destination_coord(&g, p.0, b, d)),
+            _ => None,
+        })
+        .collect::<Vec<Option<Coord>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(c) => Geom::from(Point::from(c)).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "point")
+}
+
+extendr_module! {
+    mod geodesic;
+    fn geodesic_destination;
+}
@@ -0,0 +1,305 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo::{BoundingRect, Centroid, Intersects};
+use geo_types::{coord, Coord, LineString, Point, Polygon, Rect};
+
+use crate::spatial_index::create_cached_rtree;
+use crate::utils::new_data_frame;
+
+use rayon::prelude::*;
+use rstar::RTreeObject;
+
+#[extendr]
+fn make_grid_(
+    xmin: f64,
+    ymin: f64,
+    cell_width: f64,
+    cell_height: f64,
+    n_cols: i32,
+    n_rows: i32,
+    what: &str,
+) -> Robj {
+    // the number of cells must fit in an R vector
+    let n = (n_cols as usize)
+        .checked_mul(n_rows as usize)
+        .filter(|&n| n <= i32::MAX as usize)
+        .unwrap_or_else(|| {
+            panic!("the grid has too many cells. Use a larger `cell_size` or a smaller `n`")
+        });
+
+    let mut rows: Vec<i32> = Vec::with_capacity(n);
+    let mut cols: Vec<i32> = Vec::with_capacity(n);
+    let mut cells: Vec<Robj> = Vec::with_capacity(n);
+
+    // cells are ordered from the bottom left corner by row
+    for row in 0..n_rows {
+        for col in 0..n_cols {
+            let x0 = xmin + col as f64 * cell_width;
+            let y0 = ymin + row as f64 * cell_height;
+
+            let cell = match what {
+                "polygon" => Geom::from(
+                    Rect::new(
+                        coord! { x: x0, y: y0 },
+                        coord! { x: x0 + cell_width, y: y0 + cell_height },
+                    )
+                    .to_polygon(),
+                ),
+                "point" => Geom::from(Point::new(x0 + cell_width / 2.0, y0 + cell_height / 2.0)),
+                _ => panic!("`type` must be one of \"polygon\" or \"point\""),
+            };
+
+            rows.push(row + 1);
+            cols.push(col + 1);
+            cells.push(cell.into_robj());
+        }
+    }
+
+    new_data_frame(
+        ["row", "col", "geometry"],
+        [
+            Integers::from_values(rows).into_robj(),
+            Integers::from_values(cols).into_robj(),
+            as_rsgeo_vctr(List::from_values(cells), what),
+        ],
+        n,
+    )
+}
+
+// a pointy topped hexagon with a circumradius of `size`
+fn hexagon(center: Coord, size: f64) -> Polygon {
+    let crds = (0..=6)
+        .map(|i| {
+            let angle = (30.0 + 60.0 * (i % 6) as f64).to_radians();
+            coord! {
+                x: center.x + size * angle.cos(),
+                y: center.y + size * angle.sin(),
+            }
+        })
+        .collect::<Vec<Coord>>();
+
+    Polygon::new(LineString::new(crds), vec![])
+}
+
+// Hexagons are pointy topped and `cell_size` is the distance between the
+// centers of neighboring cells. The first cell is centered on the bottom
+// left corner and every other row is shifted right by half a cell.
+#[extendr]
+fn make_hexgrid_(xmin: f64, ymin: f64, xmax: f64, ymax: f64, cell_size: f64) -> Robj {
+    let size = cell_size / 3_f64.sqrt();
+    let row_height = 1.5 * size;
+
+    // each row fully covers the band within half the circumradius of its center
+    let n_rows = ((ymax - ymin - size / 2.0) / row_height).ceil().max(0.0) as i32 + 1;
+
+    let mut q: Vec<i32> = Vec::new();
+    let mut r: Vec<i32> = Vec::new();
+    let mut centers: Vec<Robj> = Vec::new();
+    let mut cells: Vec<Robj> = Vec::new();
+
+    for row in 0..n_rows {
+        let is_odd = row % 2 == 1;
+        let offset = if is_odd { cell_size / 2.0 } else { 0.0 };
+        let y = ymin + row as f64 * row_height;
+
+        // odd rows need an additional cell on the left to cover `xmin`
+        let mut col = if is_odd { -1 } else { 0 };
+
+        loop {
+            let x = xmin + offset + col as f64 * cell_size;
+            let center = coord! { x: x, y: y };
+
+            // convert from offset to axial coordinates
+            q.push(col - row.div_euclid(2));
+            r.push(row);
+            centers.push(Geom::from(Point::from(center)).into_robj());
+            cells.push(Geom::from(hexagon(center, size)).into_robj());
+
+            if x + cell_size / 2.0 >= xmax {
+                break;
+            }
+
+            col += 1;
+        }
+    }
+
+    let n = cells.len();
+
+    new_data_frame(
+        ["q", "r", "center", "geometry"],
+        [
+            Integers::from_values(q).into_robj(),
+            Integers::from_values(r).into_robj(),
+            as_rsgeo_vctr(List::from_values(centers), "point"),
+            as_rsgeo_vctr(List::from_values(cells), "polygon"),
+        ],
+        n,
+    )
+}
+
+#[extendr]
+/// Count Points in Grid Cells
+///
+/// Counts the number of points that fall in each cell of a grid. This is the
+/// core of heatmap and hexbin workflows and pairs well with [`make_grid()`]
+/// and [`make_hexgrid()`].
+///
+/// @param points an object of class `rs_POINT`
+/// @param grid an object of class `rs_POLYGON` or `rs_MULTIPOLYGON`
+///
+/// @details
+///
+/// Candidate cells are found using an R*-tree of the grid so large point sets
+/// can be binned efficiently. Cells do not need to be from a regular grid. A
+/// point that falls on a boundary shared by multiple cells is counted only
+/// once, in the first of the cells. Missing points are ignored.
+///
+/// @export
+/// @returns
+/// An integer vector of the same length as `grid`. Missing cells are `NA`.
+/// @examples
+/// pnts <- geom_point(runif(100, 0, 10), runif(100, 0, 10))
+/// grid <- make_hexgrid(pnts, 2)
+/// grid$n <- point_density(pnts, grid$geometry)
+/// head(grid)
+fn point_density(points: List, grid: List) -> Integers {
+    if !points.inherits("rs_POINT") {
+        panic!("`points` must be an `rs_POINT`")
+    }
+
+    if !(grid.inherits("rs_POLYGON") || grid.inherits("rs_MULTIPOLYGON")) {
+        panic!("`grid` must be an `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    let is_missing = grid
+        .iter()
+        .map(|(_, xi)| xi.is_null())
+        .collect::<Vec<bool>>();
+
+    let tree = create_cached_rtree(grid);
+
+    let cells = geometry_from_list(points)
+        .into_par_iter()
+        .filter_map(|pi| {
+            let pi = Geom::from(pi?);
+            let env = pi.envelope();
+
+            tree.locate_in_envelope_intersecting(&env)
+                .filter(|cnd| cnd.geom().geom.intersects(&pi.geom))
+                .map(|cnd| cnd.data)
+                .min()
+        })
+        .collect::<Vec<usize>>();
+
+    let mut counts = vec![0_i32; is_missing.len()];
+    cells.into_iter().for_each(|i| counts[i] += 1);
+
+    counts
+        .into_iter()
+        .zip(is_missing)
+        .map(|(n, missing)| if missing { Rint::na() } else { Rint::from(n) })
+        .collect::<Integers>()
+}
+
+// The 0-based index of the tile that contains `v`. Tiles that can't be
+// represented as an R integer are missing.
+fn tile(v: f64, origin: f64, size: f64) -> Option<i32> {
+    let t = ((v - origin) / size).floor();
+
+    if t.is_finite() && t > i32::MIN as f64 && t <= i32::MAX as f64 {
+        Some(t as i32)
+    } else {
+        None
+    }
+}
+
+#[extendr]
+fn tile_index_(
+    x: List,
+    origin_x: f64,
+    origin_y: f64,
+    tile_width: f64,
+    tile_height: f64,
+    bbox: bool,
+) -> Robj {
+    if !x.inherits("rsgeo") {
+        panic!("`x` must be an `rsgeo` object")
+    }
+
+    let geoms = geometry_from_list(x);
+
+    if !bbox {
+        let n = geoms.len();
+        let tiles = geoms
+            .into_par_iter()
+            .map(|xi| {
+                let c = xi?.centroid()?;
+                Some((
+                    tile(c.x(), origin_x, tile_width),
+                    tile(c.y(), origin_y, tile_height),
+                ))
+            })
+            .collect::<Vec<Option<(Option<i32>, Option<i32>)>>>();
+
+        let (cols, rows): (Vec<Option<i32>>, Vec<Option<i32>>) =
+            tiles.into_iter().map(|ti| ti.unwrap_or_default()).unzip();
+
+        return Integers::from_values(cols.into_iter().chain(rows))
+            .into_robj()
+            .set_attrib("dim", [n as i32, 2])
+            .unwrap()
+            .set_attrib(
+                "dimnames",
+                list!(NULL, Strings::from_values(["col", "row"])),
+            )
+            .unwrap();
+    }
+
+    // every tile between the tiles of the lower left and upper right corners
+    let tiles = geoms
+        .into_par_iter()
+        .enumerate()
+        .flat_map_iter(|(i, xi)| {
+            let corners = xi.and_then(|g| g.bounding_rect()).and_then(|r| {
+                let (min, max) = (r.min(), r.max());
+                Some((
+                    tile(min.x, origin_x, tile_width)?,
+                    tile(min.y, origin_y, tile_height)?,
+                    tile(max.x, origin_x, tile_width)?,
+                    tile(max.y, origin_y, tile_height)?,
+                ))
+            });
+
+            let (col0, row0, col1, row1) = corners.unwrap_or((0, 0, -1, -1));
+
+            (row0..=row1).flat_map(move |row| (col0..=col1).map(move |col| (i, col, row)))
+        })
+        .collect::<Vec<(usize, i32, i32)>>();
+
+    let n = tiles.len();
+    let values = tiles
+        .iter()
+        .map(|(i, ..)| *i as i32 + 1)
+        .chain(tiles.iter().map(|(_, col, _)| *col))
+        .chain(tiles.iter().map(|(.., row)| *row))
+        .collect::<Vec<i32>>();
+
+    Integers::from_values(values)
+        .into_robj()
+        .set_attrib("dim", [n as i32, 3])
+        .unwrap()
+        .set_attrib(
+            "dimnames",
+            list!(NULL, Strings::from_values(["id", "col", "row"])),
+        )
+        .unwrap()
+}
+
+extendr_module! {
+    mod grid;
+    fn make_grid_;
+    fn make_hexgrid_;
+    fn point_density;
+    fn tile_index_;
+}
@@ -1,8 +1,11 @@
 use extendr_api::prelude::*;
 use geo::prelude::*;
 use geo::{EuclideanLength, Geometry};
+use geo_types::LineString;
 use rayon::prelude::*;
-use sfconversions::{geometry_from_list, Geom};
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use crate::geodesic::{as_geodesic, geodesic_length};
 
 #[extendr]
 /// Calculate LineString Length
@@ -14,11 +17,15 @@ use sfconversions::{geometry_from_list, Geom};
 ///
 /// * Vicenty, Geodesic, and Haversine methods will return in units of meters.
 /// * Geodesic length will always converge and is more accurate than the Vicenty methods.
+/// * Geodesic length is calculated on the ellipsoid given by `ellipsoid`. See [`geodesic_destination()`] for the named ellipsoids.
 /// * Haversine uses a mean earth radius of 6371.088 km.
 ///
 /// See [`geo`](https://docs.rs/geo/latest/geo/index.html#length) docs for more details.
 ///
 /// @param x an object of class `rsgeo`
+/// @param ellipsoid the name of an ellipsoid or a numeric vector of its
+///   semi-major axis in meters and flattening. One of `"WGS84"`, `"GRS80"`,
+///   `"sphere"`, `"moon"`, or `"mars"`. Defaults to `"WGS84"`.
 ///
 /// @examples
 /// set.seed(0)
@@ -29,6 +36,7 @@ use sfconversions::{geometry_from_list, Geom};
 ///
 /// length_euclidean(ln)
 /// length_geodesic(ln)
+/// length_geodesic(ln, ellipsoid = "sphere")
 /// length_vincenty(ln)
 /// length_haversine(ln)
 /// @export
@@ -60,19 +68,22 @@ fn length_euclidean(x: List) -> Doubles {
 #[extendr]
 /// @export
 /// @rdname length
-fn length_geodesic(x: List) -> Doubles {
+fn length_geodesic(x: List, #[default = "'WGS84'"] ellipsoid: Robj) -> Doubles {
     if !x.inherits("rsgeo") {
         panic!("`x` must be an object of class `rsgeo`")
     }
 
+    let g = as_geodesic(&ellipsoid);
     let x = geometry_from_list(x);
 
     let res_vec = x
         .into_par_iter()
         .map(|xi| match xi {
-            Some(Geometry::Line(geom)) => Some(geom.geodesic_length()),
-            Some(Geometry::LineString(geom)) => Some(geom.geodesic_length()),
-            Some(Geometry::MultiLineString(geom)) => Some(geom.geodesic_length()),
+            Some(Geometry::Line(geom)) => Some(geodesic_length(&g, &LineString::from(geom))),
+            Some(Geometry::LineString(geom)) => Some(geodesic_length(&g, &geom)),
+            Some(Geometry::MultiLineString(geom)) => {
+                Some(geom.iter().map(|l| geodesic_length(&g, l)).sum())
+            }
             _ => None,
         })
         .collect::<Vec<Option<f64>>>();
@@ -148,10 +159,65 @@ fn length_vincenty(x: List) -> Doubles {
     //     .collect::<Doubles>()
 }
 
+#[extendr]
+/// Extract the Longest LineString
+///
+/// Extracts the component LineString with the longest length from each
+/// MultiLineString, discarding the rest. This is useful for picking a
+/// representative part of a feature, e.g. the main stem of a river.
+///
+/// @param x an object of class `rs_LINESTRING` or `rs_MULTILINESTRING`
+///
+/// @details
+///
+/// Length is calculated using [`length_euclidean()`]. When multiple
+/// LineStrings share the longest length, the first is returned. LineStrings
+/// are returned as is.
+///
+/// @export
+/// @returns
+/// An object of class `rs_LINESTRING`. Empty MultiLineStrings are returned as
+/// missing.
+/// @examples
+/// lns <- geom_linestring(c(0, 1, 0, 10), c(0, 0, 1, 1), id = c(1, 1, 2, 2))
+/// mlns <- combine_geoms(lns)
+/// longest_linestring(mlns)
+fn longest_linestring(x: List) -> Robj {
+    if !(x.inherits("rs_LINESTRING") || x.inherits("rs_MULTILINESTRING")) {
+        panic!("`x` must be of class `rs_LINESTRING` or `rs_MULTILINESTRING`")
+    }
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| match xi? {
+            Geometry::LineString(l) => Some(l),
+            Geometry::MultiLineString(ml) => ml.0.into_iter().reduce(|longest, l| {
+                if l.euclidean_length() > longest.euclidean_length() {
+                    l
+                } else {
+                    longest
+                }
+            }),
+            _ => None,
+        })
+        .collect::<Vec<Option<LineString>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "linestring")
+}
+
 extendr_module! {
     mod length;
     fn length_euclidean;
     fn length_geodesic;
     fn length_vincenty;
     fn length_haversine;
+    fn longest_linestring;
 }
@@ -1,21 +1,41 @@
 // module imports
+mod affine;
+mod allocate;
+mod antimeridian;
 mod area;
+mod bbox;
 mod boundary;
 mod casting;
+mod cell_index;
+mod clip;
+mod cluster;
 mod coord_utils;
 mod densify;
 mod distance;
+mod format;
+mod geodesic;
+mod grid;
 mod length;
+mod line;
+mod linear_referencing;
+mod network;
+mod offset;
+mod polylabel;
 mod query;
+mod read;
 mod segmentize;
 mod similarity;
 mod simplification;
+mod skeleton;
+mod snap;
 // mod io;
 mod construction;
 mod coords;
 mod spatial_index;
 mod topology;
+mod triangulate;
 mod union;
+mod validation;
 
 use extendr_api::prelude::*;
 pub use sfconversions::{fromsf::sfc_to_rsgeo, vctrs::*, Geom};
@@ -220,19 +240,15 @@ fn haversine_intermediate(x: List, y: List, distance: Doubles) -> Robj {
     let mut res: Vec<Robj> = Vec::with_capacity(n);
 
     for i in 0..n {
-        // early return for missing distance
-        let d = distance[i];
-        if d.is_na() || d.is_infinite() || d.is_nan() {
-            return NULL.into_robj();
-        }
-
         // cycle through the points
         let (_, xi) = x_cycle.next().unwrap();
         let (_, yi) = y_cycle.next().unwrap();
 
-        // early return if either xi or yi are null
-        if xi.is_null() || yi.is_null() {
-            return NULL.into_robj();
+        // missing distances or points result in a missing point
+        let d = distance[i];
+        if !d.is_real() || xi.is_null() || yi.is_null() {
+            res.push(NULL.into_robj());
+            continue;
         }
 
         let xi = Point::from(Geom::try_from(xi).unwrap());
@@ -283,20 +299,41 @@ extendr_module! {
     fn centroids;
     fn haversine_destination;
     fn haversine_intermediate;
+    use affine;
+    use allocate;
+    use antimeridian;
     use area;
+    use bbox;
     use boundary;
     use coords;
     use coord_utils;
     use construction;
     use densify;
     use distance;
+    use format;
+    use geodesic;
+    use grid;
     use length;
+    use line;
+    use linear_referencing;
+    use network;
+    use offset;
+    use polylabel;
     use query;
+    use read;
     use segmentize;
     use simplification;
+    use skeleton;
     use similarity;
+    use snap;
+    use spatial_index;
     use topology;
+    use triangulate;
     use union;
+    use validation;
     use utils;
     use casting;
+    use cell_index;
+    use clip;
+    use cluster;
 }
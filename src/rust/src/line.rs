@@ -0,0 +1,139 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo::EuclideanLength;
+use geo_types::{coord, Coord, Geometry, Line, LineString, Point};
+
+use crate::construction::IsReal;
+
+// The lines of an `rs_LINE` vector. LineStrings must have exactly two
+// coordinates so that they describe a single line.
+fn lines_from_list(x: List) -> Vec<Option<Line>> {
+    if !(x.inherits("rs_LINE") || x.inherits("rs_LINESTRING")) {
+        panic!("`x` must be an `rs_LINE` or `rs_LINESTRING`")
+    }
+
+    geometry_from_list(x)
+        .into_iter()
+        .map(|xi| match xi? {
+            Geometry::Line(l) => Some(l),
+            Geometry::LineString(l) if l.0.len() == 2 => Some(Line::new(l.0[0], l.0[1])),
+            Geometry::LineString(_) => {
+                panic!("`x` must only contain LineStrings with two coordinates")
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn points_from_coords(x: Vec<Option<Coord>>) -> Robj {
+    let res = x
+        .into_iter()
+        .map(|xi| match xi {
+            Some(c) => Geom::from(Point::from(c)).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "point")
+}
+
+#[extendr]
+fn rs_lines_(x0: Doubles, y0: Doubles, x1: Doubles, y1: Doubles) -> Robj {
+    let n = x0.len();
+
+    if [y0.len(), x1.len(), y1.len()].iter().any(|&l| l != n) {
+        panic!("`start` and `end` must have the same number of rows")
+    }
+
+    let res = (0..n)
+        .map(|i| {
+            let vals = [x0[i], y0[i], x1[i], y1[i]];
+
+            if vals.iter().all(|v| v.is_real()) {
+                let l = Line::new(
+                    coord! { x: vals[0].inner(), y: vals[1].inner() },
+                    coord! { x: vals[2].inner(), y: vals[3].inner() },
+                );
+                Geom::from(Geometry::from(l)).into_robj()
+            } else {
+                NULL.into_robj()
+            }
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "line")
+}
+
+#[extendr]
+/// @export
+/// @rdname rs_lines
+fn line_start(x: List) -> Robj {
+    let res = lines_from_list(x)
+        .into_iter()
+        .map(|xi| xi.map(|l| l.start))
+        .collect::<Vec<Option<Coord>>>();
+
+    points_from_coords(res)
+}
+
+#[extendr]
+/// @export
+/// @rdname rs_lines
+fn line_end(x: List) -> Robj {
+    let res = lines_from_list(x)
+        .into_iter()
+        .map(|xi| xi.map(|l| l.end))
+        .collect::<Vec<Option<Coord>>>();
+
+    points_from_coords(res)
+}
+
+#[extendr]
+/// @export
+/// @rdname rs_lines
+fn line_slope(x: List) -> Doubles {
+    let res = lines_from_list(x)
+        .into_iter()
+        .map(|xi| xi.map(|l| l.slope()))
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res)
+}
+
+#[extendr]
+/// @export
+/// @rdname rs_lines
+fn line_length(x: List) -> Doubles {
+    let res = lines_from_list(x)
+        .into_iter()
+        .map(|xi| xi.map(|l| l.euclidean_length()))
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res)
+}
+
+#[extendr]
+/// @export
+/// @rdname rs_lines
+fn line_to_linestring(x: List) -> Robj {
+    let res = lines_from_list(x)
+        .into_iter()
+        .map(|xi| match xi {
+            Some(l) => Geom::from(LineString::from(l)).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "linestring")
+}
+
+extendr_module! {
+    mod line;
+    fn rs_lines_;
+    fn line_start;
+    fn line_end;
+    fn line_slope;
+    fn line_length;
+    fn line_to_linestring;
+}
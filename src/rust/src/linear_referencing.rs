@@ -0,0 +1,658 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo::{BoundingRect, EuclideanDistance, EuclideanLength, LineLocatePoint};
+use geo_types::{coord, Coord, Line, LineString, Point};
+
+use crate::construction::IsReal;
+use crate::distance::expand_envelope;
+use crate::spatial_index::create_cached_rtree;
+use crate::utils::new_data_frame;
+use rayon::prelude::*;
+use rstar::RTreeObject;
+
+// the coordinate a fraction of the way along a line
+fn interpolate_line(x: &Line, fraction: f64) -> Coord {
+    coord! {
+        x: x.start.x + x.dx() * fraction,
+        y: x.start.y + x.dy() * fraction,
+    }
+}
+
+// Extracts the portion of a LineString between two distances along it.
+// `from` and `to` must be between 0 and the length of the line with
+// `from` less than or equal to `to`.
+pub fn substring_linestring(x: &LineString, from: f64, to: f64) -> Option<LineString> {
+    let mut crds: Vec<Coord> = Vec::new();
+    let mut cum_dist = 0.0;
+
+    for line in x.lines() {
+        let len = line.euclidean_length();
+        let seg_start = cum_dist;
+        let seg_end = cum_dist + len;
+        cum_dist = seg_end;
+
+        if seg_end < from {
+            continue;
+        }
+
+        let frac = |d: f64| {
+            if len == 0.0 {
+                0.0
+            } else {
+                ((d - seg_start) / len).clamp(0.0, 1.0)
+            }
+        };
+
+        if crds.is_empty() {
+            crds.push(interpolate_line(&line, frac(from)));
+        }
+
+        if seg_end < to {
+            crds.push(line.end);
+        } else {
+            crds.push(interpolate_line(&line, frac(to)));
+            break;
+        }
+    }
+
+    crds.dedup();
+
+    match crds.len() {
+        0 => None,
+        // a substring of length 0 is a degenerate LineString
+        1 => Some(LineString::new(vec![crds[0]; 2])),
+        _ => Some(LineString::new(crds)),
+    }
+}
+
+// The coordinate at a distance along a LineString. The distance is
+// clamped to the start and end of the LineString.
+fn coord_at_distance(x: &LineString, distance: f64) -> Option<Coord> {
+    let mut cum_dist = 0.0;
+
+    if distance <= 0.0 {
+        return x.0.first().copied();
+    }
+
+    for line in x.lines() {
+        let len = line.euclidean_length();
+
+        if cum_dist + len >= distance && len > 0.0 {
+            return Some(interpolate_line(&line, (distance - cum_dist) / len));
+        }
+
+        cum_dist += len;
+    }
+
+    x.0.last().copied()
+}
+
+// The z value at a distance along a LineString interpolated between the z
+// values of its coordinates. The distance is clamped to the start and end.
+fn z_at_distance(x: &LineString, z: &[f64], distance: f64) -> Option<f64> {
+    let mut cum_dist = 0.0;
+
+    if distance <= 0.0 {
+        return z.first().copied();
+    }
+
+    for (i, line) in x.lines().enumerate() {
+        let len = line.euclidean_length();
+
+        if cum_dist + len >= distance && len > 0.0 {
+            let frac = (distance - cum_dist) / len;
+            return Some(z[i] + (z[i + 1] - z[i]) * frac);
+        }
+
+        cum_dist += len;
+    }
+
+    z.last().copied()
+}
+
+// recycles a length 1 vector to `n`
+fn recycle(x: Doubles, n: usize, arg: &str) -> Doubles {
+    let n_arg = x.len();
+
+    if n_arg == 1 {
+        Doubles::from_values(vec![x[0]; n])
+    } else if n_arg != n {
+        panic!("`{arg}` must be the same length as `x` or length 1")
+    } else {
+        x
+    }
+}
+
+#[extendr]
+/// Extract a Substring of a LineString
+///
+/// Extracts the portion of each LineString between two fractions of its
+/// length, like PostGIS' `ST_LineSubstring`. This is the core of linear
+/// referencing workflows such as extracting the segment of a route between
+/// two mileposts.
+///
+/// @param x an object of class `rs_LINESTRING`
+/// @param start_fraction,end_fraction a numeric vector of length 1 or the same
+///   length as `x`. Must be between 0 and 1 inclusive and `start_fraction`
+///   must be less than or equal to `end_fraction`.
+///
+/// @details
+///
+/// Distances are calculated using Euclidean length. When `start_fraction`
+/// and `end_fraction` are equal a LineString with two identical coordinates
+/// is returned.
+///
+/// @export
+/// @returns
+/// An object of class `rs_LINESTRING`.
+/// @examples
+/// x <- geom_linestring(c(0, 10, 10), c(0, 0, 10))
+/// line_substring(x, 0.25, 0.75)
+fn line_substring(x: List, start_fraction: Doubles, end_fraction: Doubles) -> Robj {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let n_x = x.len();
+    let n_s = start_fraction.len();
+    let n_e = end_fraction.len();
+
+    if (n_s != n_x) && (n_s != 1) {
+        panic!("`start_fraction` must be the same length as `x` or length 1")
+    } else if (n_e != n_x) && (n_e != 1) {
+        panic!("`end_fraction` must be the same length as `x` or length 1")
+    }
+
+    let start_fraction = if n_s == 1 {
+        Doubles::from_values(vec![start_fraction[0]; n_x])
+    } else {
+        start_fraction
+    };
+
+    let end_fraction = if n_e == 1 {
+        Doubles::from_values(vec![end_fraction[0]; n_x])
+    } else {
+        end_fraction
+    };
+
+    let fractions = start_fraction
+        .iter()
+        .zip(end_fraction.iter())
+        .map(|(si, ei)| {
+            if !si.is_real() || !ei.is_real() {
+                return None;
+            }
+
+            let (si, ei) = (si.inner(), ei.inner());
+
+            if !(0.0..=1.0).contains(&si) || !(0.0..=1.0).contains(&ei) {
+                panic!("`start_fraction` and `end_fraction` must be between 0 and 1")
+            } else if si > ei {
+                panic!("`start_fraction` must be less than or equal to `end_fraction`")
+            }
+
+            Some((si, ei))
+        })
+        .collect::<Vec<Option<(f64, f64)>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(fractions.into_par_iter())
+        .map(|(xi, fi)| {
+            let (si, ei) = fi?;
+            let l = LineString::try_from(xi?).unwrap();
+            let len = l.euclidean_length();
+            substring_linestring(&l, si * len, ei * len)
+        })
+        .collect::<Vec<Option<LineString>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(l) => Geom::from(l).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "linestring")
+}
+
+#[extendr]
+/// Linear Referencing by Distance
+///
+/// Finds locations along a LineString using absolute distances rather than
+/// fractions of its length. This is more natural when working with known
+/// measurements e.g. the point 500 meters along a route.
+///
+/// @param x an object of class `rs_LINESTRING`
+/// @param distance,from,to a numeric vector of length 1 or the same length as `x`.
+///   Distances are in the units of the geometry.
+///
+/// @details
+///
+/// Distances are calculated using Euclidean length. Distances less than 0 or
+/// greater than the length of the LineString are clamped to its start and end
+/// respectively. For `substring_by_distance()`, `from` must be less than or
+/// equal to `to`.
+///
+/// @export
+/// @rdname interpolate_by_distance
+/// @returns
+/// `interpolate_by_distance()` returns an object of class `rs_POINT` and
+/// `substring_by_distance()` returns an object of class `rs_LINESTRING`.
+/// @examples
+/// x <- geom_linestring(c(0, 10, 10), c(0, 0, 10))
+/// interpolate_by_distance(x, 5)
+/// substring_by_distance(x, 5, 15)
+fn interpolate_by_distance(x: List, distance: Doubles) -> Robj {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let distance = recycle(distance, x.len(), "distance");
+    let distance = distance
+        .iter()
+        .map(|di| if di.is_na() || di.is_nan() { None } else { Some(di.inner()) })
+        .collect::<Vec<Option<f64>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(distance.into_par_iter())
+        .map(|(xi, di)| {
+            let l = LineString::try_from(xi?).unwrap();
+            coord_at_distance(&l, di?)
+        })
+        .collect::<Vec<Option<Coord>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(c) => Geom::from(Point::from(c)).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "point")
+}
+
+#[extendr]
+/// @export
+/// @rdname interpolate_by_distance
+fn substring_by_distance(x: List, from: Doubles, to: Doubles) -> Robj {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let n_x = x.len();
+    let from = recycle(from, n_x, "from");
+    let to = recycle(to, n_x, "to");
+
+    let distances = from
+        .iter()
+        .zip(to.iter())
+        .map(|(fi, ti)| {
+            if fi.is_na() || fi.is_nan() || ti.is_na() || ti.is_nan() {
+                return None;
+            }
+
+            let (fi, ti) = (fi.inner(), ti.inner());
+
+            if fi > ti {
+                panic!("`from` must be less than or equal to `to`")
+            }
+
+            Some((fi, ti))
+        })
+        .collect::<Vec<Option<(f64, f64)>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(distances.into_par_iter())
+        .map(|(xi, di)| {
+            let (fi, ti) = di?;
+            let l = LineString::try_from(xi?).unwrap();
+            let len = l.euclidean_length();
+            substring_linestring(&l, fi.clamp(0.0, len), ti.clamp(0.0, len))
+        })
+        .collect::<Vec<Option<LineString>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(l) => Geom::from(l).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "linestring")
+}
+
+#[extendr]
+/// Split LineStrings at Points
+///
+/// Splits each LineString at the locations of points projected onto it.
+/// This is useful for breaking routes at stops or intersections.
+///
+/// @param x an object of class `rs_LINESTRING`
+/// @param points an object of class `rs_POINT`
+/// @param tolerance the maximum distance a point can be from a LineString to
+///   be used to split it. Points further away are ignored.
+///
+/// @details
+///
+/// Every point in `points` is considered for every LineString in `x`. A point
+/// within `tolerance` of a LineString is projected onto its closest location
+/// on the LineString which becomes the end of one piece and the start of the
+/// next. Points that project onto the start or end of a LineString do not
+/// split it. Distances are Euclidean.
+///
+/// @export
+/// @returns
+/// A list the same length as `x`. Each element is an `rs_LINESTRING` vector of
+/// the pieces of the LineString in order from its start to its end. Missing
+/// LineStrings result in `NULL`.
+/// @examples
+/// x <- geom_linestring(c(0, 10, 10), c(0, 0, 10))
+/// points <- geom_point(c(5, 11, 20), c(1, 5, 20))
+/// split_line_at_points(x, points, 1)
+fn split_line_at_points(x: List, points: List, tolerance: f64) -> List {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    } else if !points.inherits("rs_POINT") {
+        panic!("`points` must be an `rs_POINT`")
+    }
+
+    if tolerance.is_nan() || tolerance < 0.0 {
+        panic!("`tolerance` must be a non-negative number")
+    }
+
+    let tree = create_cached_rtree(points);
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let xi = xi?;
+
+            // empty LineStrings have no envelope and nothing to split
+            xi.bounding_rect()?;
+
+            let env = expand_envelope(&Geom::from(xi.clone()).envelope(), tolerance);
+            let l = LineString::try_from(xi).unwrap();
+            let len = l.euclidean_length();
+
+            let mut cuts = tree
+                .locate_in_envelope_intersecting(&env)
+                .filter_map(|cnd| {
+                    let p = Point::try_from(cnd.geom().geom.clone()).unwrap();
+
+                    if l.euclidean_distance(&p) > tolerance {
+                        return None;
+                    }
+
+                    let d = l.line_locate_point(&p)? * len;
+
+                    if d > 0.0 && d < len {
+                        Some(d)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<f64>>();
+
+            cuts.sort_by(f64::total_cmp);
+            cuts.dedup();
+
+            let mut bounds = Vec::with_capacity(cuts.len() + 2);
+            bounds.push(0.0);
+            bounds.extend(cuts);
+            bounds.push(len);
+
+            let pieces = bounds
+                .windows(2)
+                .filter_map(|w| substring_linestring(&l, w[0], w[1]))
+                .collect::<Vec<LineString>>();
+
+            Some(pieces)
+        })
+        .collect::<Vec<Option<Vec<LineString>>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(pieces) => {
+                let pieces = pieces
+                    .into_iter()
+                    .map(|p| Geom::from(p).into_robj())
+                    .collect::<Vec<Robj>>();
+
+                as_rsgeo_vctr(List::from_values(pieces), "linestring")
+            }
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    List::from_values(res)
+}
+
+#[extendr]
+/// Split LineStrings in Two
+///
+/// Splits each LineString at a fraction of its length and returns both the
+/// portion before and the portion after the cut. Use [`line_substring()`] to
+/// extract only one of them.
+///
+/// @param x an object of class `rs_LINESTRING`
+/// @param fraction a numeric vector of length 1 or the same length as `x`.
+///   Must be between 0 and 1 inclusive.
+///
+/// @details
+///
+/// Distances are calculated using Euclidean length. The cut point is the last
+/// coordinate of the first half and the first coordinate of the second half
+/// so the halves can be joined back into the original LineString. Cutting at
+/// 0 or 1 results in a half with two identical coordinates.
+///
+/// @export
+/// @returns
+/// A list the same length as `x`. Each element is an `rs_LINESTRING` vector of
+/// length 2 with the portions before and after the cut. Missing LineStrings
+/// and fractions result in `NULL`.
+/// @examples
+/// x <- geom_linestring(c(0, 10, 10), c(0, 0, 10))
+/// split_line(x, 0.25)
+fn split_line(x: List, fraction: Doubles) -> List {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let fraction = recycle(fraction, x.len(), "fraction")
+        .iter()
+        .map(|fi| {
+            if !fi.is_real() {
+                None
+            } else if !(0.0..=1.0).contains(&fi.inner()) {
+                panic!("`fraction` must be between 0 and 1")
+            } else {
+                Some(fi.inner())
+            }
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(fraction.into_par_iter())
+        .map(|(xi, fi)| {
+            let fi = fi?;
+            let l = LineString::try_from(xi?).unwrap();
+            let len = l.euclidean_length();
+            let cut = fi * len;
+
+            Some([
+                substring_linestring(&l, 0.0, cut)?,
+                substring_linestring(&l, cut, len)?,
+            ])
+        })
+        .collect::<Vec<Option<[LineString; 2]>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(halves) => {
+                let halves = halves
+                    .into_iter()
+                    .map(|h| Geom::from(h).into_robj())
+                    .collect::<Vec<Robj>>();
+
+                as_rsgeo_vctr(List::from_values(halves), "linestring")
+            }
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    List::from_values(res)
+}
+
+// the fraction, distance, and coordinate of a sample along a line
+type Sample = (f64, f64, Option<Coord>);
+
+// wrapped and documented externally
+#[extendr]
+fn sample_along_line_(x: List, n: Integers) -> List {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let n_x = x.len();
+    let n_n = n.len();
+
+    if (n_n != n_x) && (n_n != 1) {
+        panic!("`n` must be the same length as `x` or length 1")
+    }
+
+    let n = (0..n_x)
+        .map(|i| {
+            let ni = if n_n == 1 { n[0] } else { n[i] };
+
+            if ni.is_na() {
+                None
+            } else if ni.inner() < 1 {
+                panic!("`n` must be a positive integer")
+            } else {
+                Some(ni.inner() as usize)
+            }
+        })
+        .collect::<Vec<Option<usize>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(n.into_par_iter())
+        .map(|(xi, ni)| {
+            let ni = ni?;
+            let l = LineString::try_from(xi?).unwrap();
+            let len = l.euclidean_length();
+
+            let samples = (0..ni)
+                .map(|i| {
+                    let fraction = if ni == 1 {
+                        0.0
+                    } else {
+                        i as f64 / (ni - 1) as f64
+                    };
+                    let distance = fraction * len;
+                    (fraction, distance, coord_at_distance(&l, distance))
+                })
+                .collect::<Vec<Sample>>();
+
+            Some(samples)
+        })
+        .collect::<Vec<Option<Vec<Sample>>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| {
+            let samples = match xi {
+                Some(s) => s,
+                None => return NULL.into_robj(),
+            };
+
+            let n_rows = samples.len();
+            let fraction = samples.iter().map(|s| s.0).collect::<Vec<f64>>();
+            let distance = samples.iter().map(|s| s.1).collect::<Vec<f64>>();
+            let x_crds = samples
+                .iter()
+                .map(|s| s.2.map(|c| c.x))
+                .collect::<Vec<Option<f64>>>();
+            let y_crds = samples
+                .iter()
+                .map(|s| s.2.map(|c| c.y))
+                .collect::<Vec<Option<f64>>>();
+
+            new_data_frame(
+                ["fraction", "distance", "x", "y"],
+                [
+                    Doubles::from_values(fraction).into_robj(),
+                    Doubles::from_values(distance).into_robj(),
+                    Doubles::from_values(x_crds).into_robj(),
+                    Doubles::from_values(y_crds).into_robj(),
+                ],
+                n_rows,
+            )
+        })
+        .collect::<Vec<Robj>>();
+
+    List::from_values(res)
+}
+
+#[extendr]
+fn interpolate_z_(x: List, z: List, fraction: Doubles) -> Doubles {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let n = x.len();
+
+    if z.len() != n {
+        panic!("`z` must be the same length as `x`")
+    }
+
+    let fraction = recycle(fraction, n, "fraction");
+    let fraction = fraction
+        .iter()
+        .map(|fi| if fi.is_real() { Some(fi.inner()) } else { None })
+        .collect::<Vec<Option<f64>>>();
+
+    let z = z
+        .iter()
+        .map(|(_, zi)| zi.as_real_vector())
+        .collect::<Vec<Option<Vec<f64>>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(z.into_par_iter())
+        .zip(fraction.into_par_iter())
+        .map(|((xi, zi), fi)| {
+            let l = LineString::try_from(xi?).unwrap();
+            let zi = zi?;
+
+            if zi.len() != l.0.len() {
+                panic!("`z` must have a value for every coordinate of `x`")
+            }
+
+            z_at_distance(&l, &zi, fi?.clamp(0.0, 1.0) * l.euclidean_length())
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    Doubles::from_values(res_vec)
+}
+
+extendr_module! {
+    mod linear_referencing;
+    fn line_substring;
+    fn interpolate_by_distance;
+    fn substring_by_distance;
+    fn split_line_at_points;
+    fn split_line;
+    fn sample_along_line_;
+    fn interpolate_z_;
+}
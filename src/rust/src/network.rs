@@ -0,0 +1,275 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo::{EuclideanLength, LinesIter};
+use geo_types::{coord, Coord, Geometry, Line, MultiLineString};
+
+use crate::union::{coord_key, merge_lines, node_lines};
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+// A network of noded segments. `edges` are the indices of the nodes at the
+// start and end of each segment.
+struct Network {
+    segments: Vec<Line>,
+    edges: Vec<(usize, usize)>,
+    adjacency: Vec<Vec<(usize, f64)>>,
+}
+
+impl Network {
+    fn new(x: Vec<Line>) -> Self {
+        let segments = node_lines(x);
+        let mut nodes: HashMap<(u64, u64), usize> = HashMap::new();
+        let mut adjacency: Vec<Vec<(usize, f64)>> = Vec::new();
+
+        let mut node_id = |c: &Coord, adjacency: &mut Vec<Vec<(usize, f64)>>| {
+            *nodes.entry(coord_key(c)).or_insert_with(|| {
+                adjacency.push(Vec::new());
+                adjacency.len() - 1
+            })
+        };
+
+        let edges = segments
+            .iter()
+            .map(|l| {
+                let (a, b) = (
+                    node_id(&l.start, &mut adjacency),
+                    node_id(&l.end, &mut adjacency),
+                );
+                let len = l.euclidean_length();
+                adjacency[a].push((b, len));
+                adjacency[b].push((a, len));
+                (a, b)
+            })
+            .collect::<Vec<(usize, usize)>>();
+
+        Self {
+            segments,
+            edges,
+            adjacency,
+        }
+    }
+
+    // The index of the segment closest to `x` and the distance along it to
+    // the closest point.
+    fn locate(&self, x: Coord) -> Option<(usize, f64)> {
+        self.segments
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                let len2 = l.dx().powi(2) + l.dy().powi(2);
+                let u = (((x.x - l.start.x) * l.dx() + (x.y - l.start.y) * l.dy()) / len2)
+                    .clamp(0.0, 1.0);
+                let (px, py) = (l.start.x + u * l.dx(), l.start.y + u * l.dy());
+                (i, u * len2.sqrt(), (px - x.x).hypot(py - x.y))
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2).then(a.0.cmp(&b.0)))
+            .map(|(i, along, _)| (i, along))
+    }
+}
+
+// a node to visit in Dijkstra's algorithm. The closest node is visited first.
+#[derive(PartialEq)]
+struct Visit {
+    distance: f64,
+    node: usize,
+}
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// The shortest distance along the network from `origin` to every node. The
+// search stops at `max_distance` so more distant nodes are infinite.
+fn shortest_distances(x: &Network, origin: (usize, f64), max_distance: f64) -> Vec<f64> {
+    let mut dist = vec![f64::INFINITY; x.adjacency.len()];
+    let mut queue = BinaryHeap::new();
+
+    let (seg, along) = origin;
+    let (a, b) = x.edges[seg];
+    let len = x.segments[seg].euclidean_length();
+
+    for (node, d) in [(a, along), (b, len - along)] {
+        if d <= max_distance && d < dist[node] {
+            dist[node] = d;
+            queue.push(Visit { distance: d, node });
+        }
+    }
+
+    while let Some(Visit { distance, node }) = queue.pop() {
+        if distance > dist[node] {
+            continue;
+        }
+
+        for &(next, len) in x.adjacency[node].iter() {
+            let d = distance + len;
+
+            if d <= max_distance && d < dist[next] {
+                dist[next] = d;
+                queue.push(Visit {
+                    distance: d,
+                    node: next,
+                });
+            }
+        }
+    }
+
+    dist
+}
+
+// the portions of the network within `max_distance` of `origin`
+fn reachable(x: &Network, origin: (usize, f64), max_distance: f64) -> MultiLineString {
+    let dist = shortest_distances(x, origin, max_distance);
+    let mut pieces: Vec<Line> = Vec::new();
+
+    for (i, l) in x.segments.iter().enumerate() {
+        let (a, b) = x.edges[i];
+        let len = l.euclidean_length();
+
+        // intervals of distance along the segment that can be reached
+        let mut intervals = vec![
+            (0.0, max_distance - dist[a]),
+            (len - (max_distance - dist[b]), len),
+        ];
+
+        if i == origin.0 {
+            intervals.push((origin.1 - max_distance, origin.1 + max_distance));
+        }
+
+        let mut intervals = intervals
+            .into_iter()
+            .map(|(from, to)| (from.max(0.0), to.min(len)))
+            .filter(|(from, to)| from < to)
+            .collect::<Vec<(f64, f64)>>();
+
+        intervals.sort_by(|p, q| p.0.total_cmp(&q.0));
+
+        let mut merged: Vec<(f64, f64)> = Vec::with_capacity(intervals.len());
+        for (from, to) in intervals {
+            match merged.last_mut() {
+                Some(last) if from <= last.1 => last.1 = last.1.max(to),
+                _ => merged.push((from, to)),
+            }
+        }
+
+        // the ends of segments are kept exactly so that pieces stay connected
+        let coord_at = |d: f64| {
+            if d <= 0.0 {
+                l.start
+            } else if d >= len {
+                l.end
+            } else {
+                coord! { x: l.start.x + l.dx() * d / len, y: l.start.y + l.dy() * d / len }
+            }
+        };
+
+        pieces.extend(
+            merged
+                .into_iter()
+                .map(|(from, to)| Line::new(coord_at(from), coord_at(to))),
+        );
+    }
+
+    merge_lines(pieces)
+}
+
+#[extendr]
+/// Network Distance Buffer
+///
+/// Finds the parts of a network of LineStrings that can be reached from an
+/// origin by traveling no further than a maximum distance along the network.
+/// This is the basis of network isochrones such as the area that can be
+/// reached within a 10 minute walk.
+///
+/// @param origin an object of class `rs_POINT`
+/// @param lines an object of class `rs_LINESTRING` or `rs_MULTILINESTRING`
+/// @param max_distance a single non-negative number. The maximum distance to
+///   travel along the network.
+///
+/// @details
+///
+/// `lines` are noded at their intersections to create the network so travel
+/// is possible between any LineStrings that cross or touch. Each origin is
+/// snapped to the closest point on the network and travel starts from there.
+/// The distance from the origin to the network is not counted. Distances are
+/// Euclidean.
+///
+/// Segments that can only be partially traveled are cut at `max_distance`.
+///
+/// @export
+/// @returns
+/// An object of class `rs_MULTILINESTRING` with the same length as `origin`.
+/// Missing origins and empty networks result in missing geometries.
+/// @examples
+/// lines <- geom_linestring(
+///   c(0, 10, 5, 5, 10, 10),
+///   c(0, 0, -5, 5, 0, 10),
+///   c(1, 1, 2, 2, 3, 3)
+/// )
+/// network_distance_buffer(geom_point(0, 0), lines, 8)
+fn network_distance_buffer(origin: List, lines: List, max_distance: f64) -> Robj {
+    if !origin.inherits("rs_POINT") {
+        panic!("`origin` must be an `rs_POINT`")
+    } else if !(lines.inherits("rs_LINESTRING") || lines.inherits("rs_MULTILINESTRING")) {
+        panic!("`lines` must be an `rs_LINESTRING` or `rs_MULTILINESTRING`")
+    }
+
+    if max_distance.is_nan() || max_distance < 0.0 {
+        panic!("`max_distance` must be a non-negative number")
+    }
+
+    // zero length segments are dropped
+    let segments = geometry_from_list(lines)
+        .into_iter()
+        .flatten()
+        .flat_map(|g| match g {
+            Geometry::LineString(l) => l.lines().collect::<Vec<Line>>(),
+            Geometry::MultiLineString(ml) => ml.lines_iter().collect::<Vec<Line>>(),
+            _ => vec![],
+        })
+        .filter(|l| l.start != l.end)
+        .collect::<Vec<Line>>();
+
+    let network = Network::new(segments);
+
+    let res_vec = geometry_from_list(origin)
+        .into_par_iter()
+        .map(|oi| {
+            let start = match oi? {
+                Geometry::Point(p) => network.locate(p.0)?,
+                _ => return None,
+            };
+
+            Some(reachable(&network, start, max_distance))
+        })
+        .collect::<Vec<Option<MultiLineString>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(ml) => Geom::from(ml).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "multilinestring")
+}
+
+extendr_module! {
+    mod network;
+    fn network_distance_buffer;
+}
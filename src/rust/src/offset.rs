@@ -0,0 +1,144 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo_types::{coord, Coord, Geometry, LineString, Polygon};
+
+use crate::construction::IsReal;
+use rayon::prelude::*;
+
+// the unit vector perpendicular to the left of the segment from `a` to `b`
+fn left_normal(a: Coord, b: Coord) -> Coord {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len = dx.hypot(dy);
+    coord! { x: -dy / len, y: dx / len }
+}
+
+// Offsets coordinates by `distance` to the left of their direction of travel.
+// Negative distances offset to the right. Vertices are joined with a miter
+// unless the miter would be longer than twice `distance`, in which case the
+// corner is beveled. Consecutive coordinates must be distinct.
+fn offset_coords(x: &[Coord], distance: f64) -> Vec<Coord> {
+    let normals = x
+        .windows(2)
+        .map(|w| left_normal(w[0], w[1]))
+        .collect::<Vec<Coord>>();
+
+    let n = x.len();
+    let mut res: Vec<Coord> = Vec::with_capacity(n + 2);
+
+    res.push(x[0] + normals[0] * distance);
+
+    for i in 1..n - 1 {
+        let (n1, n2) = (normals[i - 1], normals[i]);
+        let bisector = n1 + n2;
+        let bisector_len = bisector.x.hypot(bisector.y);
+
+        // cosine of half of the angle between the two normals
+        let cos_half = bisector_len / 2.0;
+
+        if cos_half < 0.5 {
+            res.push(x[i] + n1 * distance);
+            res.push(x[i] + n2 * distance);
+        } else {
+            res.push(x[i] + bisector * (distance / (bisector_len * cos_half)));
+        }
+    }
+
+    res.push(x[n - 1] + normals[n - 2] * distance);
+    res
+}
+
+fn linestring_ribbon(x: &LineString, half_width: f64) -> Option<Polygon> {
+    let mut crds = x.0.clone();
+    crds.dedup();
+
+    if crds.len() < 2 {
+        return None;
+    }
+
+    let mut ring = offset_coords(&crds, half_width);
+    ring.extend(offset_coords(&crds, -half_width).into_iter().rev());
+
+    Some(Polygon::new(LineString::new(ring), vec![]))
+}
+
+#[extendr]
+/// Convert LineStrings to Ribbons
+///
+/// Offsets each LineString to both sides and joins the offsets with flat
+/// ends to create a polygon of constant width. Ribbons are used to draw
+/// roads and rivers as areas or as simple corridors around a line.
+///
+/// @param x an object of class `rs_LINESTRING`
+/// @param half_width a numeric vector of length 1 or the same length as `x`.
+///   The distance from the line to each side of the ribbon.
+///
+/// @details
+///
+/// The sides of the ribbon are joined with a miter at each vertex. When a
+/// miter would extend more than twice `half_width` from the vertex, such as
+/// at a sharp turn, the corner is beveled instead. The ends of the ribbon are
+/// flat and do not extend past the ends of the line.
+///
+/// Ribbons of lines that turn back on themselves within `half_width`
+/// self-intersect and are not valid polygons.
+///
+/// @export
+/// @returns
+/// An object of class `rs_POLYGON`. LineStrings with fewer than 2 distinct
+/// coordinates and missing values of `half_width` result in missing polygons.
+/// @examples
+/// x <- geom_linestring(c(0, 10, 10), c(0, 0, 10))
+/// line_to_ribbon(x, 1)
+fn line_to_ribbon(x: List, half_width: Doubles) -> Robj {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let n_x = x.len();
+    let n_w = half_width.len();
+
+    if (n_x != n_w) && (n_w != 1) {
+        panic!("`half_width` must be the same length as `x` or length 1")
+    }
+
+    let half_width = (0..n_x)
+        .map(|i| {
+            let wi = if n_w == 1 {
+                half_width[0]
+            } else {
+                half_width[i]
+            };
+
+            if wi.is_real() {
+                Some(wi.inner())
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<Option<f64>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(half_width)
+        .map(|(xi, wi)| match (xi?, wi?) {
+            (Geometry::LineString(l), w) => linestring_ribbon(&l, w),
+            _ => None,
+        })
+        .collect::<Vec<Option<Polygon>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(p) => Geom::from(p).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "polygon")
+}
+
+extendr_module! {
+    mod offset;
+    fn line_to_ribbon;
+}
@@ -0,0 +1,217 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo::{BoundingRect, Centroid, Contains, EuclideanDistance};
+use geo_types::{coord, Coord, Geometry, Line, MultiPolygon, Point};
+
+use crate::utils::geometry_lines;
+use crate::utils::new_data_frame;
+
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+// a square cell used to search for the pole of inaccessibility
+struct Cell {
+    center: Coord,
+    half_size: f64,
+    // signed distance from the center to the polygon's boundary
+    distance: f64,
+    // the largest distance possible for any point within the cell
+    max_distance: f64,
+}
+
+impl Cell {
+    fn new(center: Coord, half_size: f64, polygon: &MultiPolygon, edges: &[Line]) -> Self {
+        let distance = signed_distance(center, polygon, edges);
+
+        Self {
+            center,
+            half_size,
+            distance,
+            max_distance: distance + half_size * std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_distance == other.max_distance
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// cells are ordered by their potential so the most promising is searched first
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.max_distance
+            .partial_cmp(&other.max_distance)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+// the distance to the closest edge which is negative outside of the polygon
+fn signed_distance(x: Coord, polygon: &MultiPolygon, edges: &[Line]) -> f64 {
+    let pnt = Point::from(x);
+    let dist = edges
+        .iter()
+        .map(|e| e.euclidean_distance(&pnt))
+        .fold(f64::INFINITY, f64::min);
+
+    if polygon.contains(&pnt) {
+        dist
+    } else {
+        -dist
+    }
+}
+
+// The polylabel algorithm by Mapbox. The bounding box of the polygon is
+// covered with cells which are recursively split into quarters. Cells that
+// can't contain a point more than `tolerance` further from the boundary than
+// the best point found so far are discarded.
+fn polylabel(x: &MultiPolygon, tolerance: f64) -> Option<(Coord, f64)> {
+    let bbox = x.bounding_rect()?;
+    let cell_size = bbox.width().min(bbox.height());
+
+    if cell_size == 0.0 {
+        return Some((bbox.center(), 0.0));
+    }
+
+    let edges = geometry_lines(&Geometry::MultiPolygon(x.clone()));
+    let half_size = cell_size / 2.0;
+
+    let mut queue = BinaryHeap::new();
+    let (xmin, ymin) = bbox.min().x_y();
+    let (xmax, ymax) = bbox.max().x_y();
+
+    let mut cx = xmin;
+    while cx < xmax {
+        let mut cy = ymin;
+        while cy < ymax {
+            let center = coord! { x: cx + half_size, y: cy + half_size };
+            queue.push(Cell::new(center, half_size, x, &edges));
+            cy += cell_size;
+        }
+        cx += cell_size;
+    }
+
+    // the centroid is often a good first guess
+    let mut best = match x.centroid() {
+        Some(c) => Cell::new(c.0, 0.0, x, &edges),
+        None => Cell::new(bbox.center(), 0.0, x, &edges),
+    };
+
+    let bbox_cell = Cell::new(bbox.center(), 0.0, x, &edges);
+    if bbox_cell.distance > best.distance {
+        best = bbox_cell;
+    }
+
+    while let Some(cell) = queue.pop() {
+        if cell.distance > best.distance {
+            best = Cell::new(cell.center, 0.0, x, &edges);
+        }
+
+        // no better point can be found in this cell
+        if cell.max_distance - best.distance <= tolerance {
+            continue;
+        }
+
+        let h = cell.half_size / 2.0;
+
+        for (dx, dy) in [(-h, -h), (h, -h), (-h, h), (h, h)] {
+            let center = coord! { x: cell.center.x + dx, y: cell.center.y + dy };
+            queue.push(Cell::new(center, h, x, &edges));
+        }
+    }
+
+    Some((best.center, best.distance.max(0.0)))
+}
+
+#[extendr]
+/// Find the Maximum Inscribed Circle
+///
+/// Finds the largest circle that fits inside of each polygon. The center of
+/// this circle is known as the pole of inaccessibility and is often the best
+/// location for placing a label on an irregular polygon.
+///
+/// @param x an object of class `rs_POLYGON` or `rs_MULTIPOLYGON`
+/// @param tolerance the precision of the result. The radius of the circle
+///   found is within `tolerance` of the true maximum inscribed circle.
+///
+/// @details
+///
+/// The circle is found using the polylabel algorithm by Mapbox. The bounding
+/// box of the polygon is covered with square cells which are recursively
+/// split into quarters. Cells that cannot contain a better center are
+/// discarded.
+///
+/// Unlike a centroid, the center is always within the polygon.
+///
+/// @export
+/// @returns
+/// A `data.frame` with columns `center`, an `rs_POINT` of the center of the
+/// circle, and `radius`, the radius of the circle. Missing geometries have a
+/// missing center and radius.
+/// @examples
+/// x <- c(0, 10, 10, 2, 2, 10, 10, 0, 0)
+/// y <- c(0, 0, 2, 2, 8, 8, 10, 10, 0)
+/// ply <- geom_polygon(x, y)
+/// max_inscribed_circle(ply, 0.01)
+/// centroids(ply)
+fn max_inscribed_circle(x: List, tolerance: f64) -> Robj {
+    if !(x.inherits("rs_POLYGON") || x.inherits("rs_MULTIPOLYGON")) {
+        panic!("`x` must be an `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    if tolerance.is_nan() || tolerance <= 0.0 {
+        panic!("`tolerance` must be a positive number")
+    }
+
+    let n = x.len();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let ply = match xi? {
+                Geometry::Polygon(p) => MultiPolygon::new(vec![p]),
+                Geometry::MultiPolygon(p) => p,
+                _ => unreachable!(),
+            };
+
+            polylabel(&ply, tolerance)
+        })
+        .collect::<Vec<Option<(Coord, f64)>>>();
+
+    let (centers, radii): (Vec<Robj>, Vec<Option<f64>>) = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some((c, r)) => (Geom::from(Point::from(c)).into_robj(), Some(r)),
+            None => (NULL.into_robj(), None),
+        })
+        .unzip();
+
+    new_data_frame(
+        ["center", "radius"],
+        [
+            as_rsgeo_vctr(List::from_values(centers), "point"),
+            Doubles::from_values(radii.into_iter().map(|r| match r {
+                Some(r) => Rfloat::from(r),
+                None => Rfloat::na(),
+            }))
+            .into_robj(),
+        ],
+        n,
+    )
+}
+
+extendr_module! {
+    mod polylabel;
+    fn max_inscribed_circle;
+}
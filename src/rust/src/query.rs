@@ -1,6 +1,6 @@
 use extendr_api::prelude::*;
 use sfconversions::vctrs::as_rsgeo_vctr;
-use sfconversions::Geom;
+use sfconversions::{geometry_from_list, Geom};
 
 use geo::{
     Closest, ClosestPoint, GeodesicBearing, HaversineBearing, HaversineClosestPoint, IsConvex,
@@ -8,7 +8,13 @@ use geo::{
 };
 
 use crate::construction::IsReal;
-use geo_types::{LineString, Point};
+use crate::distance::{envelope_diagonal, expand_envelope, paired_geoms};
+use crate::spatial_index::create_cached_rtree;
+use crate::utils::new_data_frame;
+use geo::{EuclideanDistance, EuclideanLength};
+use geo_types::{Geometry, LineString, Point};
+use rayon::prelude::*;
+use rstar::RTreeObject;
 
 
 #[extendr]
@@ -29,6 +35,7 @@ use geo_types::{LineString, Point};
 /// y <- geom_point(runif(10, 0, 90), rnorm(10, 1, 90))
 /// bearing_geodesic(x, y)
 /// bearing_haversine(x, y)
+/// azimuth(x, y)
 fn bearing_haversine(x: List, y: List) -> Doubles {
     if !x.inherits("rs_POINT") || !y.inherits("rs_POINT") {
         panic!("`x` and `y` must be point geometries of class `rs_POINT`");
@@ -71,6 +78,91 @@ fn bearing_geodesic(x: List, y: List) -> Doubles {
         .collect::<Doubles>()
 }
 
+#[extendr]
+/// @details
+///
+/// `bearing_geodesic()` and `bearing_haversine()` assume geographic
+/// coordinates. `azimuth()` calculates the planar angle between points with
+/// projected coordinates. It is measured in degrees clockwise from north
+/// (the positive y axis) between 0 and 360. Identical points have no
+/// azimuth and return `NA`. `x` and `y` must be the same length or either
+/// can be length 1.
+///
+/// @export
+/// @rdname bearing
+fn azimuth(x: List, y: List) -> Doubles {
+    if !x.inherits("rs_POINT") || !y.inherits("rs_POINT") {
+        panic!("`x` and `y` must be point geometries of class `rs_POINT`");
+    }
+
+    paired_geoms(x, y)
+        .into_iter()
+        .map(|pair| match pair {
+            (Some(Geometry::Point(p1)), Some(Geometry::Point(p2))) => {
+                let dx = p2.x() - p1.x();
+                let dy = p2.y() - p1.y();
+
+                if dx == 0.0 && dy == 0.0 {
+                    Rfloat::na()
+                } else {
+                    dx.atan2(dy).to_degrees().rem_euclid(360.0).into()
+                }
+            }
+            _ => Rfloat::na(),
+        })
+        .collect::<Doubles>()
+}
+
+#[extendr]
+fn linestring_bearings_(x: List, bearing_type: &str) -> List {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    if !["geodesic", "haversine", "planar"].contains(&bearing_type) {
+        panic!("`type` must be one of `geodesic`, `haversine`, or `planar`")
+    }
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let l = LineString::try_from(xi?).unwrap();
+
+            let bearings = l
+                .lines()
+                .map(|li| {
+                    let (p1, p2) = (Point::from(li.start), Point::from(li.end));
+
+                    // segments of length 0 have no direction
+                    if p1 == p2 {
+                        return None;
+                    }
+
+                    let b = match bearing_type {
+                        "geodesic" => p1.geodesic_bearing(p2),
+                        "haversine" => p1.haversine_bearing(p2),
+                        _ => li.dx().atan2(li.dy()).to_degrees().rem_euclid(360.0),
+                    };
+
+                    Some(b)
+                })
+                .collect::<Vec<Option<f64>>>();
+
+            Some(bearings)
+        })
+        .collect::<Vec<Option<Vec<Option<f64>>>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(b) => Doubles::from_values(b).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    List::from_values(res)
+}
+
 #[extendr]
 /// Find Closest Point
 ///
@@ -86,8 +178,12 @@ fn bearing_geodesic(x: List, y: List) -> Doubles {
 /// y <- geom_point(runif(10, 0, 90), rnorm(10, 1, 90))
 /// closest_point(x, y)
 /// closest_point_haversine(x, y)
+/// dist_to_geometry(x, y)
 /// @returns
-/// An `rs_POINT` vector
+/// `closest_point()` and `closest_point_haversine()` return an `rs_POINT`
+/// vector. `dist_to_geometry()` returns a numeric vector of the Euclidean
+/// distance from each point in `y` to the closest point on `x`. Points inside
+/// of a polygon have a distance of 0.
 fn closest_point(x: List, y: List) -> Robj {
     if !y.inherits("rs_POINT") {
         panic!("`y` must be point geometries of class `rs_POINT`");
@@ -151,13 +247,86 @@ fn closest_point_haversine(x: List, y: List) -> Robj {
 }
 
 #[extendr]
+/// @export
+/// @rdname closest_point
+fn dist_to_geometry(x: List, y: List) -> Doubles {
+    if !y.inherits("rs_POINT") {
+        panic!("`y` must be point geometries of class `rs_POINT`");
+    } else if !x.inherits("rsgeo") {
+        panic!("`x` must be an `rsgeo` object")
+    }
+
+    let n_x = x.len();
+    let n_y = y.len();
+    let n = n_x.max(n_y);
+
+    if (n_x != n && n_x != 1) || (n_y != n && n_y != 1) {
+        panic!("`x` and `y` must be the same length or length 1")
+    }
+
+    x.iter()
+        .cycle()
+        .zip(y.iter().cycle())
+        .take(n)
+        .map(|((_, xi), (_, yi))| {
+            if xi.is_null() || yi.is_null() {
+                Rfloat::na()
+            } else {
+                let p: Point = Geom::try_from(yi).unwrap().geom.try_into().unwrap();
+                let closest = Geom::try_from(xi).unwrap().geom.closest_point(&p);
+
+                match closest {
+                    Closest::SinglePoint(pnt) => p.euclidean_distance(&pnt).into(),
+                    Closest::Intersection(_) => 0.0.into(),
+                    Closest::Indeterminate => Rfloat::na(),
+                }
+            }
+        })
+        .collect::<Doubles>()
+}
+
+// Tests the convexity of each LineString or the exterior ring of each Polygon.
+// The rings of polygons are always closed.
+fn convexity<F: Fn(&LineString) -> bool>(x: List, f: F) -> Logicals {
+    if !(x.inherits("rs_LINESTRING") || x.inherits("rs_POLYGON")) {
+        panic!("`x` must be of class `rs_LINESTRING` or `rs_POLYGON`");
+    }
+
+    x.iter()
+        .map(|(_, xi)| {
+            if xi.is_null() {
+                Rbool::na()
+            } else {
+                match Geom::try_from(xi).unwrap().geom {
+                    Geometry::LineString(l) => f(&l).into(),
+                    Geometry::Polygon(p) => f(p.exterior()).into(),
+                    _ => unreachable!(),
+                }
+            }
+        })
+        .collect::<Logicals>()
+}
 
-/// Determine the Convexity of a LineString
+#[extendr]
+/// Determine the Convexity of a LineString or Polygon
 ///
-/// For a given `rs_LINESTRING` vector, test its convexity. Convexity can be tested
-/// strictly or strongly, as well as based on winding.
+/// For a given `rs_LINESTRING` or `rs_POLYGON` vector, test its convexity.
+/// Convexity can be tested strictly or strongly, as well as based on winding.
+/// Polygons are tested using their exterior ring.
 ///
-/// @param x an object of class `rs_LINESTRING`
+/// @param x an object of class `rs_LINESTRING` or `rs_POLYGON`
+/// @param strict default `FALSE`. If `TRUE`, consecutive collinear vertices are
+///   not allowed. This is the same as `is_strictly_convex()`.
+/// @param allow_collinear default `TRUE`. Whether a closed ring whose
+///   vertices all lie on a single line is considered convex.
+///
+/// @details
+///
+/// Data often contains redundant vertices that lie on a straight line between
+/// their neighbors. These are allowed by `is_convex()` unless `strict = TRUE`.
+/// A closed ring that is entirely collinear has no orientation. It is
+/// considered convex unless `allow_collinear = FALSE`. An open linestring
+/// whose vertices are all collinear is never convex.
 ///
 /// See [`geo` docs for further details](https://docs.rs/geo/latest/geo/algorithm/is_convex/trait.IsConvex.html)
 /// @export
@@ -171,144 +340,64 @@ fn closest_point_haversine(x: List, y: List) -> Robj {
 ///   )
 ///   
 /// is_convex(lns)
+/// is_convex(lns, strict = TRUE)
 /// is_cw_convex(lns)
 /// is_ccw_convex(lns)
 /// is_strictly_convex(lns)
 /// is_strictly_cw_convex(lns)
 /// is_strictly_ccw_convex(lns)
-fn is_convex(x: List) -> Logicals {
-    // check that y is a point
-    if !x.inherits("rs_LINESTRING") {
-        panic!("`x` must be LineString geometries of class `rs_LINESTRING`");
-    }
-
-    x.iter()
-        .map(|(_, xi)| {
-            if xi.is_null() {
-                Rbool::na()
-            } else {
-                LineString::try_from(Geom::try_from(xi).unwrap())
-                    .unwrap()
-                    .is_convex()
-                    .into()
-            }
-        })
-        .collect::<Logicals>()
+///
+/// ply <- geom_polygon(c(0, 1, 1, 0, 0), c(0, 0, 1, 1, 0))
+/// is_convex(ply)
+fn is_convex(
+    x: List,
+    #[default = "FALSE"] strict: bool,
+    #[default = "TRUE"] allow_collinear: bool,
+) -> Logicals {
+    convexity(x, |l| {
+        if l.is_collinear() {
+            l.is_closed() && allow_collinear
+        } else if strict {
+            l.is_strictly_convex()
+        } else {
+            l.is_convex()
+        }
+    })
 }
 
 #[extendr]
 /// @export
 /// @rdname convex
 fn is_ccw_convex(x: List) -> Logicals {
-    // check that y is a point
-    if !x.inherits("rs_LINESTRING") {
-        panic!("`x` must be LineString geometries of class `rs_LINESTRING`");
-    }
-
-    x.iter()
-        .map(|(_, xi)| {
-            if xi.is_null() {
-                Rbool::na()
-            } else {
-                LineString::try_from(Geom::try_from(xi).unwrap())
-                    .unwrap()
-                    .is_ccw_convex()
-                    .into()
-            }
-        })
-        .collect::<Logicals>()
+    convexity(x, |l| l.is_ccw_convex())
 }
 
 #[extendr]
 /// @export
 /// @rdname convex
 fn is_cw_convex(x: List) -> Logicals {
-    // check that y is a point
-    if !x.inherits("rs_LINESTRING") {
-        panic!("`x` must be LineString geometries of class `rs_LINESTRING`");
-    }
-
-    x.iter()
-        .map(|(_, xi)| {
-            if xi.is_null() {
-                Rbool::na()
-            } else {
-                LineString::try_from(Geom::try_from(xi).unwrap())
-                    .unwrap()
-                    .is_cw_convex()
-                    .into()
-            }
-        })
-        .collect::<Logicals>()
+    convexity(x, |l| l.is_cw_convex())
 }
 
 #[extendr]
 /// @export
 /// @rdname convex
 fn is_strictly_convex(x: List) -> Logicals {
-    // check that y is a point
-    if !x.inherits("rs_LINESTRING") {
-        panic!("`x` must be LineString geometries of class `rs_LINESTRING`");
-    }
-
-    x.iter()
-        .map(|(_, xi)| {
-            if xi.is_null() {
-                Rbool::na()
-            } else {
-                LineString::try_from(Geom::try_from(xi).unwrap())
-                    .unwrap()
-                    .is_strictly_convex()
-                    .into()
-            }
-        })
-        .collect::<Logicals>()
+    convexity(x, |l| l.is_strictly_convex())
 }
 
 #[extendr]
 /// @export
 /// @rdname convex
 fn is_strictly_ccw_convex(x: List) -> Logicals {
-    // check that y is a point
-    if !x.inherits("rs_LINESTRING") {
-        panic!("`x` must be LineString geometries of class `rs_LINESTRING`");
-    }
-
-    x.iter()
-        .map(|(_, xi)| {
-            if xi.is_null() {
-                Rbool::na()
-            } else {
-                LineString::try_from(Geom::try_from(xi).unwrap())
-                    .unwrap()
-                    .is_strictly_ccw_convex()
-                    .into()
-            }
-        })
-        .collect::<Logicals>()
+    convexity(x, |l| l.is_strictly_ccw_convex())
 }
 
 #[extendr]
 /// @export
 /// @rdname convex
 fn is_strictly_cw_convex(x: List) -> Logicals {
-    // check that y is a point
-    if !x.inherits("rs_LINESTRING") {
-        panic!("`x` must be LineString geometries of class `rs_LINESTRING`");
-    }
-
-    x.iter()
-        .map(|(_, xi)| {
-            if xi.is_null() {
-                Rbool::na()
-            } else {
-                LineString::try_from(Geom::try_from(xi).unwrap())
-                    .unwrap()
-                    .is_strictly_cw_convex()
-                    .into()
-            }
-        })
-        .collect::<Logicals>()
+    convexity(x, |l| l.is_strictly_cw_convex())
 }
 
 #[extendr]
@@ -364,6 +453,67 @@ fn line_interpolate_point(x: List, fraction: Doubles) -> Robj {
     as_rsgeo_vctr(List::from_values(res_vec), "point")
 }
 
+#[extendr]
+/// Find the Start, Middle, and End of LineStrings
+///
+/// Finds the first point, the point halfway along, and the last point of
+/// each LineString. These are commonly used for placing labels and arrows.
+///
+/// @param x an object of class `rs_LINESTRING`
+///
+/// @details
+///
+/// The midpoint is the point at half of the Euclidean length of the line as
+/// returned by `line_interpolate_point(x, 0.5)`.
+///
+/// @export
+/// @returns
+/// A `data.frame` with columns `start`, `mid`, and `end`, each an object of
+/// class `rs_POINT`. Missing geometries result in missing points.
+/// @examples
+/// x <- geom_linestring(c(0, 10, 10), c(0, 0, 10))
+/// line_key_points(x)
+fn line_key_points(x: List) -> Robj {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be a `rs_LINESTRING`")
+    }
+
+    let n = x.len();
+    let mut start: Vec<Robj> = Vec::with_capacity(n);
+    let mut mid: Vec<Robj> = Vec::with_capacity(n);
+    let mut end: Vec<Robj> = Vec::with_capacity(n);
+
+    let as_robj = |p: Option<Point>| match p {
+        Some(p) => Geom::from(p).into_robj(),
+        None => NULL.into_robj(),
+    };
+
+    for (_, xi) in x.iter() {
+        if xi.is_null() {
+            start.push(NULL.into_robj());
+            mid.push(NULL.into_robj());
+            end.push(NULL.into_robj());
+            continue;
+        }
+
+        let l: LineString = Geom::try_from(xi).unwrap().try_into().unwrap();
+
+        start.push(as_robj(l.0.first().copied().map(Point::from)));
+        mid.push(as_robj(l.line_interpolate_point(0.5)));
+        end.push(as_robj(l.0.last().copied().map(Point::from)));
+    }
+
+    new_data_frame(
+        ["start", "mid", "end"],
+        [
+            as_rsgeo_vctr(List::from_values(start), "point"),
+            as_rsgeo_vctr(List::from_values(mid), "point"),
+            as_rsgeo_vctr(List::from_values(end), "point"),
+        ],
+        n,
+    )
+}
+
 #[extendr]
 /// Locate a Point on a LineString
 ///
@@ -413,12 +563,387 @@ fn locate_point_on_line(x: List, y: List) -> Doubles {
 
 
 
+#[extendr]
+/// Calculate Vertex Angles
+///
+/// For each interior vertex of a LineString, calculates the turning angle
+/// between the incoming and outgoing segments. This is useful for detecting
+/// sharp corners and classifying curvature.
+///
+/// @param x an object of class `rs_LINESTRING`
+///
+/// @details
+///
+/// Angles are measured in degrees between -180 and 180. Positive angles are
+/// counter-clockwise (left) turns, negative angles are clockwise (right) turns,
+/// and an angle of 0 indicates that the line continues straight. Endpoints
+/// have no angle. If either segment adjacent to a vertex has a length of 0,
+/// its angle is `NA`.
+///
+/// @export
+/// @returns
+/// A list of numeric vectors. Each vector has a length of the number of
+/// coordinates in the LineString minus 2.
+/// @examples
+/// x <- geom_linestring(c(0, 1, 1, 2), c(0, 0, 1, 1))
+/// vertex_angles(x)
+fn vertex_angles(x: List) -> List {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let res_vec = x
+        .iter()
+        .map(|(_, xi)| {
+            if xi.is_null() {
+                return NULL.into_robj();
+            }
+
+            let l: LineString = Geom::try_from(xi).unwrap().geom.try_into().unwrap();
+            let lines = l.lines().collect::<Vec<_>>();
+
+            lines
+                .windows(2)
+                .map(|w| {
+                    let (l1, l2) = (w[0], w[1]);
+
+                    if (l1.dx() == 0.0 && l1.dy() == 0.0) || (l2.dx() == 0.0 && l2.dy() == 0.0) {
+                        return Rfloat::na();
+                    }
+
+                    let a1 = l1.dy().atan2(l1.dx());
+                    let a2 = l2.dy().atan2(l2.dx());
+                    let mut turn = (a2 - a1).to_degrees();
+
+                    if turn > 180.0 {
+                        turn -= 360.0;
+                    } else if turn <= -180.0 {
+                        turn += 360.0;
+                    }
+
+                    turn.into()
+                })
+                .collect::<Doubles>()
+                .into_robj()
+        })
+        .collect::<Vec<Robj>>();
+
+    List::from_values(res_vec)
+}
+
+#[extendr]
+/// Project a Point onto a LineString
+///
+/// Projects each point in `y` onto the corresponding LineString in `x`.
+/// This is the complete linear referencing operation returning the position
+/// along the line, the distance to the line, and the snapped location.
+///
+/// @param x an object of class `rs_LINESTRING`
+/// @param y an object of class `rs_POINT`
+///
+/// @export
+/// @returns
+/// A `data.frame` with the same number of rows as `x` and columns:
+///
+/// - `fraction`: the fraction of the LineString's length to the closest point.
+///   See `locate_point_on_line()`.
+/// - `distance`: the distance from the point to the LineString
+/// - `point`: an `rs_POINT` vector of the closest point on the LineString
+/// @examples
+/// x <- geom_linestring(c(-1, 0, 0), c(0, 0, 1))
+/// y <- geom_point(-0.5, 0.25)
+/// project_point(x, y)
+fn project_point(x: List, y: List) -> Robj {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    } else if !y.inherits("rs_POINT") {
+        panic!("`y` must be an `rs_POINT`")
+    }
+
+    let n_x = x.len();
+
+    if y.len() != n_x {
+        panic!("`y` must be the same length as `x`")
+    }
+
+    let mut fraction: Vec<Option<f64>> = Vec::with_capacity(n_x);
+    let mut distance: Vec<Option<f64>> = Vec::with_capacity(n_x);
+    let mut snapped: Vec<Robj> = Vec::with_capacity(n_x);
+
+    for ((_, xi), (_, yi)) in x.iter().zip(y.iter()) {
+        if xi.is_null() || yi.is_null() {
+            fraction.push(None);
+            distance.push(None);
+            snapped.push(NULL.into_robj());
+            continue;
+        }
+
+        let l: LineString = Geom::try_from(xi).unwrap().geom.try_into().unwrap();
+        let p: Point = Geom::try_from(yi).unwrap().geom.try_into().unwrap();
+
+        fraction.push(l.line_locate_point(&p));
+
+        match l.closest_point(&p) {
+            Closest::SinglePoint(pnt) | Closest::Intersection(pnt) => {
+                distance.push(Some(p.euclidean_distance(&pnt)));
+                snapped.push(Geom::from(pnt).into());
+            }
+            Closest::Indeterminate => {
+                distance.push(None);
+                snapped.push(NULL.into_robj());
+            }
+        }
+    }
+
+    new_data_frame(
+        ["fraction", "distance", "point"],
+        [
+            Doubles::from_values(fraction).into_robj(),
+            Doubles::from_values(distance).into_robj(),
+            as_rsgeo_vctr(List::from_values(snapped), "point"),
+        ],
+        n_x,
+    )
+}
+
+#[extendr]
+/// Snap Points to a Line Network
+///
+/// Snaps each point to the closest LineString in a network when one is
+/// within a maximum distance. This is the building block of map matching
+/// GPS traces to a road network.
+///
+/// @param points an object of class `rs_POINT`
+/// @param lines an object of class `rs_LINESTRING`
+/// @param max_distance the maximum distance a point can be moved in the
+///   units of the geometries.
+///
+/// @details
+///
+/// Candidate lines are found using an R*-tree of the bounding boxes of
+/// `lines`. Distances are Euclidean. When a point is equally close to more
+/// than one line, the line that comes first in `lines` is used.
+///
+/// @export
+/// @returns
+/// A `data.frame` with the same number of rows as `points` and columns:
+///
+/// - `point`: an `rs_POINT` vector of the snapped points
+/// - `line`: the index of the matched LineString in `lines`
+/// - `measure`: the distance along the matched LineString to the snapped point
+/// - `distance`: the distance from the point to the snapped point
+///
+/// Points that are missing or further than `max_distance` from every line
+/// have missing values in every column.
+/// @examples
+/// lines <- geom_linestring(c(0, 10, 0, 0), c(0, 0, 0, 10), id = c(1, 1, 2, 2))
+/// points <- geom_point(c(5, 0.5, 20), c(0.2, 8, 20))
+/// snap_to_network(points, lines, 1)
+fn snap_to_network(points: List, lines: List, max_distance: f64) -> Robj {
+    if !points.inherits("rs_POINT") {
+        panic!("`points` must be an `rs_POINT`")
+    } else if !lines.inherits("rs_LINESTRING") {
+        panic!("`lines` must be an `rs_LINESTRING`")
+    }
+
+    if max_distance.is_nan() || max_distance < 0.0 {
+        panic!("`max_distance` must be a non-negative number")
+    }
+
+    let n = points.len();
+    let tree = create_cached_rtree(lines);
+
+    let res_vec = geometry_from_list(points)
+        .into_par_iter()
+        .map(|xi| {
+            let p = Point::try_from(xi?).unwrap();
+            let env = expand_envelope(&Geom::from(p).envelope(), max_distance);
+
+            let (dist, idx, snapped, line) = tree
+                .locate_in_envelope_intersecting(&env)
+                .filter_map(|cnd| {
+                    let l = LineString::try_from(cnd.geom().geom.clone()).unwrap();
+                    match l.closest_point(&p) {
+                        Closest::SinglePoint(s) | Closest::Intersection(s) => {
+                            Some((p.euclidean_distance(&s), cnd.data, s, l))
+                        }
+                        Closest::Indeterminate => None,
+                    }
+                })
+                .filter(|(d, ..)| *d <= max_distance)
+                .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))?;
+
+            let measure = line.line_locate_point(&snapped)? * line.euclidean_length();
+            Some((snapped, idx, measure, dist))
+        })
+        .collect::<Vec<Option<(Point, usize, f64, f64)>>>();
+
+    let mut snapped: Vec<Robj> = Vec::with_capacity(n);
+    let mut line: Vec<Option<i32>> = Vec::with_capacity(n);
+    let mut measure: Vec<Option<f64>> = Vec::with_capacity(n);
+    let mut distance: Vec<Option<f64>> = Vec::with_capacity(n);
+
+    for xi in res_vec {
+        match xi {
+            Some((p, i, m, d)) => {
+                snapped.push(Geom::from(p).into());
+                line.push(Some(i as i32 + 1));
+                measure.push(Some(m));
+                distance.push(Some(d));
+            }
+            None => {
+                snapped.push(NULL.into_robj());
+                line.push(None);
+                measure.push(None);
+                distance.push(None);
+            }
+        }
+    }
+
+    new_data_frame(
+        ["point", "line", "measure", "distance"],
+        [
+            as_rsgeo_vctr(List::from_values(snapped), "point"),
+            Integers::from_values(line).into_robj(),
+            Doubles::from_values(measure).into_robj(),
+            Doubles::from_values(distance).into_robj(),
+        ],
+        n,
+    )
+}
+
+#[extendr]
+/// Find the Nearest Feature
+///
+/// Finds the nearest geometry in `y` to each point in `x` along with the
+/// closest point on it. Unlike [`closest_point()`], which pairs up the
+/// elements of two vectors, every geometry in `y` is searched. This is used to
+/// snap points to the nearest of many features such as the nearest road or
+/// building.
+///
+/// @param x an object of class `rs_POINT`
+/// @param y an object of class `rsgeo`
+///
+/// @details
+///
+/// Candidates are found using an R*-tree of `y` so that the distance to every
+/// geometry is never calculated. Distances are Euclidean. When a point is
+/// equally close to more than one geometry, the geometry that comes first in
+/// `y` is used. Points inside of a polygon have a distance of 0.
+///
+/// @export
+/// @returns
+/// A `data.frame` with the same number of rows as `x` and columns:
+///
+/// - `index`: the index of the nearest geometry in `y`
+/// - `point`: an `rs_POINT` vector of the closest point on the nearest geometry
+/// - `distance`: the distance from the point to the nearest geometry
+///
+/// Missing points and points when `y` has no non-missing geometries have
+/// missing values in every column.
+/// @examples
+/// lines <- geom_linestring(c(0, 10, 0, 0), c(0, 0, 0, 10), id = c(1, 1, 2, 2))
+/// points <- geom_point(c(5, 0.5, 20), c(0.2, 8, 20))
+/// nearest_feature(points, lines)
+fn nearest_feature(x: List, y: List) -> Robj {
+    if !x.inherits("rs_POINT") {
+        panic!("`x` must be an `rs_POINT`")
+    } else if !y.inherits("rsgeo") {
+        panic!("`y` must be an `rsgeo` object")
+    }
+
+    let n = x.len();
+    let tree = create_cached_rtree(y);
+    let n_tree = tree.size();
+
+    // the search radius starts at roughly the spacing between geometries if
+    // they were spread evenly over the extent of `y`
+    let step = if n_tree > 0 {
+        envelope_diagonal(&tree.root().envelope()) / (n_tree as f64).sqrt()
+    } else {
+        0.0
+    };
+    let step = if step > 0.0 { step } else { 1.0 };
+
+    // The radius is doubled until a geometry is found within it so that the
+    // nearest geometry is guaranteed to be among the candidates.
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            if n_tree == 0 {
+                return None;
+            }
+
+            let p = Point::try_from(xi?).unwrap();
+            let env = Geom::from(p).envelope();
+            let mut r = step;
+
+            loop {
+                let cands = tree
+                    .locate_in_envelope_intersecting(&expand_envelope(&env, r))
+                    .collect::<Vec<_>>();
+
+                let all_found = cands.len() == n_tree;
+
+                let nearest = cands
+                    .into_iter()
+                    .filter_map(|cnd| match cnd.geom().geom.closest_point(&p) {
+                        Closest::SinglePoint(s) | Closest::Intersection(s) => {
+                            Some((p.euclidean_distance(&s), cnd.data, s))
+                        }
+                        Closest::Indeterminate => None,
+                    })
+                    .filter(|(d, ..)| all_found || *d <= r)
+                    .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+                if nearest.is_some() || all_found {
+                    return nearest;
+                }
+
+                r *= 2.0;
+            }
+        })
+        .collect::<Vec<Option<(f64, usize, Point)>>>();
+
+    let mut index: Vec<Option<i32>> = Vec::with_capacity(n);
+    let mut point: Vec<Robj> = Vec::with_capacity(n);
+    let mut distance: Vec<Option<f64>> = Vec::with_capacity(n);
+
+    for xi in res_vec {
+        match xi {
+            Some((d, i, p)) => {
+                index.push(Some(i as i32 + 1));
+                point.push(Geom::from(p).into());
+                distance.push(Some(d));
+            }
+            None => {
+                index.push(None);
+                point.push(NULL.into_robj());
+                distance.push(None);
+            }
+        }
+    }
+
+    new_data_frame(
+        ["index", "point", "distance"],
+        [
+            Integers::from_values(index).into_robj(),
+            as_rsgeo_vctr(List::from_values(point), "point"),
+            Doubles::from_values(distance).into_robj(),
+        ],
+        n,
+    )
+}
+
 extendr_module! {
     mod query;
     fn bearing_geodesic;
     fn bearing_haversine;
+    fn azimuth;
+    fn linestring_bearings_;
     fn closest_point;
     fn closest_point_haversine;
+    fn dist_to_geometry;
     fn is_convex;
     fn is_ccw_convex;
     fn is_cw_convex;
@@ -426,5 +951,10 @@ extendr_module! {
     fn is_strictly_ccw_convex;
     fn is_strictly_cw_convex;
     fn line_interpolate_point;
+    fn line_key_points;
     fn locate_point_on_line;
+    fn vertex_angles;
+    fn project_point;
+    fn snap_to_network;
+    fn nearest_feature;
 }
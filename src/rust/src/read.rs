@@ -0,0 +1,198 @@
+use extendr_api::prelude::*;
+use sfconversions::{vctrs::as_rsgeo_vctr, Geom};
+
+use geo_types::Geometry;
+use geojson::FeatureReader;
+use serde_json::Value;
+use wkt::TryFromWkt;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+// the rsgeo class of a single geometry
+fn geometry_class(x: &Geometry) -> &'static str {
+    match x {
+        Geometry::Point(_) => "point",
+        Geometry::MultiPoint(_) => "multipoint",
+        Geometry::Line(_) | Geometry::LineString(_) => "linestring",
+        Geometry::MultiLineString(_) => "multilinestring",
+        Geometry::Polygon(_) | Geometry::Rect(_) | Geometry::Triangle(_) => "polygon",
+        Geometry::MultiPolygon(_) => "multipolygon",
+        Geometry::GeometryCollection(_) => "geometrycollection",
+    }
+}
+
+// Creates an rsgeo vector from optional geometries. When every geometry
+// shares a type the vector is of that type, otherwise it is `rs_GEOMETRY`.
+pub fn geoms_to_rsgeo(x: Vec<Option<Geometry>>) -> Robj {
+    let mut classes = x.iter().flatten().map(geometry_class);
+
+    let cls = match classes.next() {
+        Some(first) if classes.all(|c| c == first) => first,
+        _ => "geometry",
+    };
+
+    let res = x
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), cls)
+}
+
+#[extendr]
+fn read_wkt_file_(path: &str) -> List {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => panic!("failed to open `{path}`: {e}"),
+    };
+
+    let mut geoms: Vec<Option<Geometry>> = Vec::new();
+    let mut failures: Vec<i32> = Vec::new();
+
+    // lines are parsed as they are read so that the file's contents are
+    // never held in memory all at once
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => panic!("failed to read line {} of `{path}`: {e}", i + 1),
+        };
+
+        let line = line.trim();
+
+        // blank lines are treated as missing geometries
+        if line.is_empty() {
+            geoms.push(None);
+            continue;
+        }
+
+        match Geometry::<f64>::try_from_wkt_str(line) {
+            Ok(g) => geoms.push(Some(g)),
+            Err(_) => {
+                failures.push(i as i32 + 1);
+                geoms.push(None);
+            }
+        }
+    }
+
+    list!(
+        geometry = geoms_to_rsgeo(geoms),
+        failures = Integers::from_values(failures)
+    )
+}
+
+// Converts the values of a feature property to an R vector. Properties
+// that are all booleans or all numbers become logical and double vectors
+// respectively. Anything else becomes a character vector where arrays and
+// objects are written as JSON. `null` values are always missing.
+fn property_column(x: Vec<Value>) -> Robj {
+    let mut non_null = x.iter().filter(|v| !v.is_null());
+
+    if non_null.clone().all(|v| v.is_boolean()) {
+        let res = x
+            .iter()
+            .map(|v| match v {
+                Value::Bool(b) => Rbool::from(*b),
+                _ => Rbool::na(),
+            })
+            .collect::<Vec<Rbool>>();
+
+        Logicals::from_values(res).into_robj()
+    } else if non_null.all(|v| v.is_number()) {
+        let res = x
+            .iter()
+            .map(|v| match v.as_f64() {
+                Some(d) => Rfloat::from(d),
+                None => Rfloat::na(),
+            })
+            .collect::<Vec<Rfloat>>();
+
+        Doubles::from_values(res).into_robj()
+    } else {
+        let res = x
+            .into_iter()
+            .map(|v| match v {
+                Value::Null => Rstr::na(),
+                Value::String(s) => Rstr::from(s),
+                v => Rstr::from(v.to_string()),
+            })
+            .collect::<Vec<Rstr>>();
+
+        Strings::from_values(res).into_robj()
+    }
+}
+
+#[extendr]
+fn read_geojson_file_(path: &str) -> List {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => panic!("failed to open `{path}`: {e}"),
+    };
+
+    let mut geoms: Vec<Option<Geometry>> = Vec::new();
+
+    // property columns in the order their keys are first seen
+    let mut keys: Vec<String> = Vec::new();
+    let mut columns: Vec<Vec<Value>> = Vec::new();
+    let mut key_index: HashMap<String, usize> = HashMap::new();
+
+    // features are read one at a time rather than parsing the entire
+    // FeatureCollection into memory
+    let features = FeatureReader::from_reader(BufReader::new(file)).features();
+
+    for (i, feature) in features.enumerate() {
+        let feature = match feature {
+            Ok(f) => f,
+            Err(e) => panic!("failed to read feature {} of `{path}`: {e}", i + 1),
+        };
+
+        geoms.push(
+            feature
+                .geometry
+                .and_then(|g| Geometry::<f64>::try_from(g).ok()),
+        );
+
+        for (key, value) in feature.properties.unwrap_or_default() {
+            let idx = *key_index.entry(key.clone()).or_insert_with(|| {
+                // keys that haven't been seen are missing for previous features
+                keys.push(key);
+                columns.push(vec![Value::Null; i]);
+                columns.len() - 1
+            });
+
+            columns[idx].push(value);
+        }
+
+        // keys that this feature doesn't have are missing
+        for col in columns.iter_mut() {
+            if col.len() == i {
+                col.push(Value::Null);
+            }
+        }
+    }
+
+    let n = geoms.len();
+    let values = columns
+        .into_iter()
+        .map(property_column)
+        .collect::<Vec<Robj>>();
+
+    let properties = List::from_names_and_values(keys, values)
+        .unwrap()
+        .set_class(["data.frame"])
+        .unwrap()
+        .set_attrib("row.names", (1..=n as i32).collect::<Vec<i32>>())
+        .unwrap();
+
+    list!(geometry = geoms_to_rsgeo(geoms), properties = properties)
+}
+
+extendr_module! {
+    mod read;
+    fn read_wkt_file_;
+    fn read_geojson_file_;
+}
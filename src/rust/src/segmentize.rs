@@ -1,9 +1,9 @@
 use extendr_api::prelude::*; 
 use rayon::prelude::*;
-use geo::{LineStringSegmentize, LineStringSegmentizeHaversine};
+use geo::{GeodesicBearing, GeodesicDestination, LineStringSegmentize, LineStringSegmentizeHaversine};
 use sfconversions::{Geom, geometry_from_list, vctrs::as_rsgeo_vctr};
 
-use geo_types::{LineString, MultiLineString};
+use geo_types::{LineString, MultiLineString, Point};
 
 // wrapped and documented externally
 #[extendr]
@@ -26,14 +26,15 @@ fn line_segmentize_(x: List, n: Integers) -> Robj {
         .into_par_iter()
         .zip(n.into_par_iter())
         .map(|(xi, ni)| {
-            if ni.is_na() {
+            if ni.is_na() || ni.inner() < 1 {
                 None
             } else {
-                match xi {
-                    Some(g) => LineString::try_from(g)
-                        .unwrap()
-                        .line_segmentize(ni.inner() as usize),
-                    None => None,
+                // LineStrings without a segment can't be divided
+                let l = LineString::try_from(xi?).unwrap();
+                if l.0.len() < 2 {
+                    None
+                } else {
+                    l.line_segmentize(ni.inner() as usize)
                 }
             }
         })
@@ -71,14 +72,15 @@ fn line_segmentize_haversine_(x: List, n: Integers) -> Robj {
         .into_par_iter()
         .zip(n.into_par_iter())
         .map(|(xi, ni)| {
-            if ni.is_na() {
+            if ni.is_na() || ni.inner() < 1 {
                 None
             } else {
-                match xi {
-                    Some(g) => LineString::try_from(g)
-                        .unwrap()
-                        .line_segmentize_haversine(ni.inner() as usize),
-                    None => None,
+                // LineStrings without a segment can't be divided
+                let l = LineString::try_from(xi?).unwrap();
+                if l.0.len() < 2 {
+                    None
+                } else {
+                    l.line_segmentize_haversine(ni.inner() as usize)
                 }
             }
         })
@@ -95,8 +97,59 @@ fn line_segmentize_haversine_(x: List, n: Integers) -> Robj {
     as_rsgeo_vctr(List::from_values(res), "multilinestring")
 }
 
+// Adds coordinates along the geodesic between each pair of coordinates so
+// that none are more than `max_distance` meters apart.
+fn segmentize_geodesic_linestring(x: &LineString, max_distance: f64) -> LineString {
+    let mut crds = Vec::with_capacity(x.0.len());
+
+    for line in x.lines() {
+        let (start, end) = (Point::from(line.start), Point::from(line.end));
+        let (bearing, distance) = start.geodesic_bearing_distance(end);
+        let n = (distance / max_distance).ceil().max(1.0) as usize;
+
+        crds.push(line.start);
+        for i in 1..n {
+            let dist = distance * i as f64 / n as f64;
+            crds.push(start.geodesic_destination(bearing, dist).0);
+        }
+    }
+
+    if let Some(last) = x.0.last() {
+        crds.push(*last);
+    }
+
+    LineString::new(crds)
+}
+
+// wrapped and documented externally
+#[extendr]
+fn segmentize_geodesic_(x: List, max_distance: f64) -> Robj {
+    if !max_distance.is_finite() || max_distance <= 0.0 {
+        panic!("`max_distance_m` must be a positive number")
+    }
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let l = LineString::try_from(xi?).unwrap();
+            Some(segmentize_geodesic_linestring(&l, max_distance))
+        })
+        .collect::<Vec<Option<LineString>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(xi) => Geom::from(xi).into_robj(),
+            None => ().into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "linestring")
+}
+
 extendr_module!{
     mod segmentize; 
     fn line_segmentize_;
     fn line_segmentize_haversine_;
+    fn segmentize_geodesic_;
 }
\ No newline at end of file
@@ -1,7 +1,14 @@
 use extendr_api::prelude::*;
 use geo::FrechetDistance;
-use geo_types::LineString;
-use sfconversions::Geom;
+use geo_types::{Coord, Geometry, LineString, Polygon};
+use sfconversions::{
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, rsgeo_type, verify_rsgeo},
+    Geom,
+};
+
+use std::collections::HashSet;
+use xxhash_rust::xxh3::xxh3_64;
 
 #[extendr]
 /// Calculate Frechet Distance
@@ -40,7 +47,401 @@ fn frechet_distance(x: List, y: List) -> Doubles {
         })
         .collect::<Doubles>()
 }
+
+fn coords_almost_equal(x: &[Coord], y: &[Coord], tolerance: f64) -> bool {
+    x.len() == y.len()
+        && x.iter()
+            .zip(y.iter())
+            .all(|(a, b)| (a.x - b.x).abs() <= tolerance && (a.y - b.y).abs() <= tolerance)
+}
+
+fn polygons_almost_equal(x: &Polygon, y: &Polygon, tolerance: f64) -> bool {
+    coords_almost_equal(&x.exterior().0, &y.exterior().0, tolerance)
+        && x.interiors().len() == y.interiors().len()
+        && x.interiors()
+            .iter()
+            .zip(y.interiors().iter())
+            .all(|(a, b)| coords_almost_equal(&a.0, &b.0, tolerance))
+}
+
+// compares the structure of two geometries exactly and
+// their coordinates within an absolute tolerance
+pub fn almost_equals(x: &Geometry, y: &Geometry, tolerance: f64) -> bool {
+    match (x, y) {
+        (Geometry::Point(a), Geometry::Point(b)) => coords_almost_equal(&[a.0], &[b.0], tolerance),
+        (Geometry::Line(a), Geometry::Line(b)) => {
+            coords_almost_equal(&[a.start, a.end], &[b.start, b.end], tolerance)
+        }
+        (Geometry::MultiPoint(a), Geometry::MultiPoint(b)) => {
+            let a = a.iter().map(|p| p.0).collect::<Vec<Coord>>();
+            let b = b.iter().map(|p| p.0).collect::<Vec<Coord>>();
+            coords_almost_equal(&a, &b, tolerance)
+        }
+        (Geometry::LineString(a), Geometry::LineString(b)) => {
+            coords_almost_equal(&a.0, &b.0, tolerance)
+        }
+        (Geometry::MultiLineString(a), Geometry::MultiLineString(b)) => {
+            a.0.len() == b.0.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(a, b)| coords_almost_equal(&a.0, &b.0, tolerance))
+        }
+        (Geometry::Polygon(a), Geometry::Polygon(b)) => polygons_almost_equal(a, b, tolerance),
+        (Geometry::MultiPolygon(a), Geometry::MultiPolygon(b)) => {
+            a.0.len() == b.0.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(a, b)| polygons_almost_equal(a, b, tolerance))
+        }
+        (Geometry::Rect(a), Geometry::Rect(b)) => {
+            coords_almost_equal(&[a.min(), a.max()], &[b.min(), b.max()], tolerance)
+        }
+        (Geometry::Triangle(a), Geometry::Triangle(b)) => {
+            coords_almost_equal(&a.to_array(), &b.to_array(), tolerance)
+        }
+        (Geometry::GeometryCollection(a), Geometry::GeometryCollection(b)) => {
+            a.0.len() == b.0.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(a, b)| almost_equals(a, b, tolerance))
+        }
+        _ => false,
+    }
+}
+
+#[extendr]
+/// Compare Geometries Within a Tolerance
+///
+/// Tests whether geometries are equal coordinate by coordinate within an
+/// absolute tolerance. This is useful for comparing the results of floating
+/// point algorithms such as simplification or reprojection.
+///
+/// @param x an object of class `rsgeo`
+/// @param y an object of class `rsgeo` of length 1 or the same length as `x`
+/// @param tolerance the maximum absolute difference allowed between the x
+///   and y values of each pair of coordinates.
+///
+/// @details
+///
+/// The structure of the geometries must match exactly: they must be the same
+/// geometry type with the same number of components and coordinates in the
+/// same order. Only the coordinate values are compared within the tolerance.
+///
+/// @export
+/// @returns
+/// A logical vector of the same length as `x`. Comparisons with missing
+/// geometries are `NA`.
+/// @examples
+/// x <- geom_linestring(1:3, c(0, 1, 0))
+/// y <- geom_linestring(1:3, c(0, 1.0001, 0))
+/// geom_almost_equals(x, y, 0.001)
+/// geom_almost_equals(x, y, 1e-6)
+fn geom_almost_equals(x: List, y: List, tolerance: f64) -> Logicals {
+    verify_rsgeo(&x);
+    verify_rsgeo(&y);
+
+    if tolerance.is_nan() || tolerance < 0.0 {
+        panic!("`tolerance` must be a non-negative number")
+    }
+
+    let n_x = x.len();
+    let n_y = y.len();
+
+    if (n_x != n_y) && (n_y != 1) {
+        panic!("`y` must be the same length as `x` or length 1")
+    }
+
+    x.iter()
+        .zip(y.iter().cycle())
+        .map(|((_, xi), (_, yi))| {
+            if xi.is_null() || yi.is_null() {
+                Rbool::na()
+            } else {
+                let xi = <&Geom>::from_robj(&xi).unwrap();
+                let yi = <&Geom>::from_robj(&yi).unwrap();
+                almost_equals(&xi.geom, &yi.geom, tolerance).into()
+            }
+        })
+        .collect::<Logicals>()
+}
+
+// A coordinate value as bits. With a tolerance the value is snapped to a
+// grid with cells of that size first.
+fn quantize(x: f64, tolerance: f64) -> u64 {
+    if tolerance == 0.0 {
+        (x + 0.0).to_bits()
+    } else {
+        (x / tolerance).round() as i64 as u64
+    }
+}
+
+fn push_coords<'a>(
+    key: &mut Vec<u64>,
+    x: impl ExactSizeIterator<Item = &'a Coord>,
+    tolerance: f64,
+) {
+    key.push(x.len() as u64);
+    for c in x {
+        key.push(quantize(c.x, tolerance));
+        key.push(quantize(c.y, tolerance));
+    }
+}
+
+fn push_polygon(key: &mut Vec<u64>, x: &Polygon, tolerance: f64) {
+    key.push(x.interiors().len() as u64 + 1);
+    for ring in std::iter::once(x.exterior()).chain(x.interiors()) {
+        push_coords(key, ring.0.iter(), tolerance);
+    }
+}
+
+// Encodes the type, structure, and coordinates of a geometry so that
+// geometries are equal when their keys are equal.
+fn geometry_key(x: &Geometry, tolerance: f64, key: &mut Vec<u64>) {
+    match x {
+        Geometry::Point(p) => {
+            key.push(0);
+            push_coords(key, std::iter::once(&p.0), tolerance);
+        }
+        Geometry::MultiPoint(mp) => {
+            key.push(1);
+            push_coords(key, mp.0.iter().map(|p| &p.0), tolerance);
+        }
+        Geometry::Line(l) => {
+            key.push(2);
+            push_coords(key, [l.start, l.end].iter(), tolerance);
+        }
+        Geometry::LineString(l) => {
+            key.push(2);
+            push_coords(key, l.0.iter(), tolerance);
+        }
+        Geometry::MultiLineString(ml) => {
+            key.push(3);
+            key.push(ml.0.len() as u64);
+            for l in ml.iter() {
+                push_coords(key, l.0.iter(), tolerance);
+            }
+        }
+        Geometry::Polygon(p) => {
+            key.push(4);
+            push_polygon(key, p, tolerance);
+        }
+        Geometry::Rect(r) => {
+            key.push(4);
+            push_polygon(key, &r.to_polygon(), tolerance);
+        }
+        Geometry::Triangle(t) => {
+            key.push(4);
+            push_polygon(key, &t.to_polygon(), tolerance);
+        }
+        Geometry::MultiPolygon(mp) => {
+            key.push(5);
+            key.push(mp.0.len() as u64);
+            for p in mp.iter() {
+                push_polygon(key, p, tolerance);
+            }
+        }
+        Geometry::GeometryCollection(gc) => {
+            key.push(6);
+            key.push(gc.0.len() as u64);
+            for g in gc.iter() {
+                geometry_key(g, tolerance, key);
+            }
+        }
+    }
+}
+
+// whether each geometry is equal to one earlier in the vector
+fn duplicated(x: &List, tolerance: f64) -> Vec<bool> {
+    if tolerance.is_nan() || tolerance < 0.0 {
+        panic!("`tolerance` must be a non-negative number")
+    }
+
+    let mut seen: HashSet<Vec<u64>> = HashSet::with_capacity(x.len());
+    let mut seen_missing = false;
+
+    x.iter()
+        .map(|(_, xi)| {
+            if xi.is_null() {
+                return std::mem::replace(&mut seen_missing, true);
+            }
+
+            let mut key = Vec::new();
+            geometry_key(&<&Geom>::from_robj(&xi).unwrap().geom, tolerance, &mut key);
+            !seen.insert(key)
+        })
+        .collect()
+}
+
+#[extendr]
+/// Find Duplicate Geometries
+///
+/// Identifies geometries that are equal to a geometry earlier in the vector.
+/// `geom_duplicated()` and `geom_unique()` are the geometry equivalents of
+/// `duplicated()` and `unique()`.
+///
+/// @param x an object of class `rsgeo`
+/// @param tolerance the size of the grid that coordinates are snapped to
+///   before comparison. The default of `0` compares coordinates exactly.
+///
+/// @details
+///
+/// Geometries are equal when they have the same type, the same structure, and
+/// the same coordinates in the same order. Each geometry is hashed so that
+/// duplicates are found in a single pass rather than comparing every pair.
+///
+/// When `tolerance` is greater than 0, coordinates are rounded to the nearest
+/// multiple of `tolerance`. Coordinates that are within `tolerance` of each
+/// other but round to different multiples are not considered equal.
+///
+/// Like `duplicated()`, missing geometries are duplicates of earlier missing
+/// geometries.
+///
+/// @export
+/// @returns
+/// `geom_duplicated()` returns a logical vector the same length as `x`.
+/// `geom_unique()` returns `x` without its duplicates.
+/// @examples
+/// x <- geom_point(c(0, 1, 0, 1.0001), c(0, 1, 0, 1))
+/// geom_duplicated(x)
+/// geom_unique(x)
+/// geom_unique(x, tolerance = 0.001)
+fn geom_duplicated(x: List, #[default = "0"] tolerance: f64) -> Logicals {
+    verify_rsgeo(&x);
+
+    let res = duplicated(&x, tolerance)
+        .into_iter()
+        .map(Rbool::from)
+        .collect::<Vec<Rbool>>();
+
+    Logicals::from_values(res)
+}
+
+#[extendr]
+/// @export
+/// @rdname geom_duplicated
+fn geom_unique(x: List, #[default = "0"] tolerance: f64) -> Robj {
+    verify_rsgeo(&x);
+
+    let cls = rsgeo_type(&x);
+    let res = x
+        .iter()
+        .zip(duplicated(&x, tolerance))
+        .filter(|(_, dup)| !dup)
+        .map(|((_, xi), _)| xi)
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), cls.as_str())
+}
+
+#[extendr]
+/// Hash Geometries
+///
+/// Calculates a hash of each geometry's type, structure, and coordinates.
+/// Hashes are much cheaper to compare than geometries so they can be used as
+/// join keys, cache keys, or to find duplicates across vectors.
+///
+/// @param x an object of class `rsgeo`
+/// @param tolerance the size of the grid that coordinates are snapped to
+///   before hashing. The default of `0` hashes coordinates exactly.
+///
+/// @details
+///
+/// Geometries are encoded in the same way as `geom_duplicated()`. Each value
+/// of the encoding is written as little-endian bytes and hashed with the
+/// 64-bit XXH3 algorithm. The hash of a geometry is therefore the same across
+/// sessions and platforms. Negative and positive zero coordinates have the
+/// same hash.
+///
+/// Different geometries can have the same hash, though this is extremely
+/// unlikely.
+///
+/// @export
+/// @returns
+/// A character vector of 16 digit hexadecimal hashes with the same length as
+/// `x`. Missing geometries are `NA`.
+/// @examples
+/// x <- geom_point(c(0, 1, 0), c(0, 1, 0))
+/// geom_hash(x)
+fn geom_hash(x: List, #[default = "0"] tolerance: f64) -> Strings {
+    verify_rsgeo(&x);
+
+    if tolerance.is_nan() || tolerance < 0.0 {
+        panic!("`tolerance` must be a non-negative number")
+    }
+
+    x.iter()
+        .map(|(_, xi)| {
+            if xi.is_null() {
+                return Rstr::na();
+            }
+
+            let mut key = Vec::new();
+            geometry_key(&<&Geom>::from_robj(&xi).unwrap().geom, tolerance, &mut key);
+
+            let bytes = key
+                .into_iter()
+                .flat_map(u64::to_le_bytes)
+                .collect::<Vec<u8>>();
+
+            Rstr::from(format!("{:016x}", xxh3_64(&bytes)))
+        })
+        .collect::<Strings>()
+}
+
+#[extendr]
+/// Canonicalize LineString Direction
+///
+/// Reverses LineStrings so that every LineString runs in the same canonical
+/// direction regardless of the direction it was digitized in. Lines that
+/// follow the same path in opposite directions are then identical, which
+/// makes `geom_duplicated()`, `geom_hash()`, and other comparisons
+/// direction-insensitive.
+///
+/// @param x an object of class `rs_LINESTRING`
+///
+/// @details
+///
+/// LineStrings start from the endpoint with the smaller x value, or the
+/// smaller y value when the x values are equal. LineStrings whose endpoints
+/// are equal, such as closed rings, are left as they are.
+///
+/// @export
+/// @returns
+/// An object of class `rs_LINESTRING`. Missing geometries stay missing.
+/// @examples
+/// x <- geom_linestring(c(0, 1, 2, 2, 1, 0), c(0, 1, 0, 0, 1, 0), rep(1:2, each = 3))
+/// geom_duplicated(x)
+/// geom_duplicated(canonicalize_direction(x))
+fn canonicalize_direction(x: List) -> Robj {
+    if !x.inherits("rs_LINESTRING") {
+        panic!("`x` must be an `rs_LINESTRING`")
+    }
+
+    let res = geometry_from_list(x)
+        .into_iter()
+        .map(|xi| match xi {
+            Some(Geometry::LineString(mut l)) => {
+                if let (Some(first), Some(last)) = (l.0.first(), l.0.last()) {
+                    if (last.x, last.y) < (first.x, first.y) {
+                        l.0.reverse();
+                    }
+                }
+
+                Geom::from(l).into_robj()
+            }
+            _ => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "linestring")
+}
+
 extendr_module! {
     mod similarity;
     fn frechet_distance;
+    fn geom_almost_equals;
+    fn geom_duplicated;
+    fn geom_unique;
+    fn geom_hash;
+    fn canonicalize_direction;
 }
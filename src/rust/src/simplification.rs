@@ -1,11 +1,17 @@
 use extendr_api::prelude::*;
-use geo::{Simplify, SimplifyVw, SimplifyVwPreserve};
-use geo_types::Geometry;
+use geo::{CoordsIter, Simplify, SimplifyVw, SimplifyVwPreserve};
+use geo_types::{coord, Coord, Geometry, LineString, MultiLineString, MultiPolygon, Polygon};
+use rayon::prelude::*;
 use sfconversions::{
-    vctrs::{as_rsgeo_vctr, rsgeo_type},
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, rsgeo_type, verify_rsgeo},
     Geom,
 };
 
+use crate::union::coord_key;
+use crate::validation::geometry_problem;
+use std::collections::{HashMap, HashSet};
+
 #[extendr]
 fn simplify_geoms_(x: List, epsilon: Doubles) -> Robj {
     let n_e = epsilon.len();
@@ -133,9 +139,648 @@ fn simplify_vw_preserve_geoms_(x: List, epsilon: Doubles) -> Robj {
     as_rsgeo_vctr(List::from_values(res_vec), cls.as_str())
 }
 
+// Binary search the Visvalingam-Whyatt threshold that results in at most
+// `max_points` coordinates. The smallest threshold that satisfies the budget
+// is used so that as much detail as possible is retained. Endpoints and the
+// minimal rings can't be removed so the budget may not be reachable, in which
+// case the most simplified geometry is returned.
+fn simplify_vw_to_n<G>(geom: G, max_points: usize) -> G
+where
+    G: SimplifyVw<f64> + CoordsIter<Scalar = f64>,
+{
+    if geom.coords_count() <= max_points {
+        return geom;
+    }
+
+    let (xmin, ymin, xmax, ymax) = geom.coords_iter().fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(xmin, ymin, xmax, ymax), crd| {
+            (xmin.min(crd.x), ymin.min(crd.y), xmax.max(crd.x), ymax.max(crd.y))
+        },
+    );
+
+    // no triangle formed by the coordinates can have a larger
+    // area than the bounding box so this removes all removable points
+    let mut lo = 0.0;
+    let mut hi = (xmax - xmin) * (ymax - ymin) + 1.0;
+
+    if geom.simplify_vw(&hi).coords_count() > max_points {
+        return geom.simplify_vw(&hi);
+    }
+
+    for _ in 0..64 {
+        let mid = lo + (hi - lo) / 2.0;
+        if mid <= lo || mid >= hi {
+            break;
+        }
+
+        if geom.simplify_vw(&mid).coords_count() <= max_points {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    geom.simplify_vw(&hi)
+}
+
+#[extendr]
+fn simplify_to_n_(x: List, max_points: Integers) -> Robj {
+    let n_m = max_points.len();
+    let n_x = x.len();
+
+    if (n_x != n_m) && (n_m != 1) {
+        panic!("`max_points` must be the same length as `x` or length `1`");
+    }
+
+    let max_points = if n_m == 1 {
+        vec![max_points[0]; n_x]
+    } else {
+        max_points.iter().collect::<Vec<Rint>>()
+    };
+
+    // determine the input class the output must be the same type
+    let cls = rsgeo_type(&x);
+
+    // the fewest coordinates of a valid geometry of the class
+    let min_points = match cls.as_str() {
+        "point" | "multipoint" => 1,
+        "polygon" | "multipolygon" => 4,
+        _ => 2,
+    };
+
+    let max_points = max_points
+        .into_iter()
+        .map(|mi| {
+            if mi.is_na() {
+                None
+            } else if mi.inner() < min_points {
+                panic!("`max_points` must be at least {min_points} for `{cls}` geometries")
+            } else {
+                Some(mi.inner() as usize)
+            }
+        })
+        .collect::<Vec<Option<usize>>>();
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(max_points.into_par_iter())
+        .map(|(xi, mi)| {
+            let mi = mi?;
+
+            match xi? {
+                Geometry::LineString(geom) => Some(Geometry::from(simplify_vw_to_n(geom, mi))),
+                Geometry::MultiLineString(geom) => {
+                    Some(Geometry::from(simplify_vw_to_n(geom, mi)))
+                }
+                Geometry::Polygon(geom) => Some(Geometry::from(simplify_vw_to_n(geom, mi))),
+                Geometry::MultiPolygon(geom) => Some(Geometry::from(simplify_vw_to_n(geom, mi))),
+                // points can't be simplified
+                geom => Some(geom),
+            }
+        })
+        .collect::<Vec<Option<Geometry>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), cls.as_str())
+}
+
+type Key = (u64, u64);
+
+// The coordinates of a ring without its closing coordinate
+// or any repeated consecutive coordinates.
+fn ring_coords(x: &LineString) -> Vec<Coord> {
+    let mut crds = x.0.clone();
+    crds.dedup();
+
+    if crds.len() > 1 && crds.first() == crds.last() {
+        crds.pop();
+    }
+
+    crds
+}
+
+// Splits a ring into arcs that start and end at nodes. Rings without nodes
+// are a single arc that starts at their smallest coordinate so that rings
+// sharing all of their coordinates, such as a hole and the polygon filling
+// it, are split identically.
+fn ring_arcs<F: Fn(&Coord) -> bool>(x: &[Coord], is_node: F) -> Vec<Vec<Coord>> {
+    let n = x.len();
+
+    let start = match (0..n).find(|&i| is_node(&x[i])) {
+        Some(i) => i,
+        None => (0..n).min_by_key(|&i| coord_key(&x[i])).unwrap(),
+    };
+
+    let mut arcs = Vec::new();
+    let mut cur = vec![x[start]];
+
+    for j in 1..=n {
+        let c = x[(start + j) % n];
+        cur.push(c);
+
+        if j == n || is_node(&c) {
+            arcs.push(std::mem::replace(&mut cur, vec![c]));
+        }
+    }
+
+    arcs
+}
+
+// Simplifies a single arc. Closed arcs are split at the coordinate farthest
+// from their start so that they don't collapse into a line. A closed arc that
+// would no longer form a ring is left as is.
+fn simplify_arc(x: &[Coord], epsilon: f64) -> Vec<Coord> {
+    let n = x.len();
+
+    if n < 4 || x[0] != x[n - 1] {
+        return LineString::new(x.to_vec()).simplify(&epsilon).0;
+    }
+
+    let dist = |c: &Coord| (c.x - x[0].x).hypot(c.y - x[0].y);
+    let k = (1..n - 1)
+        .max_by(|&a, &b| dist(&x[a]).total_cmp(&dist(&x[b])))
+        .unwrap();
+
+    let mut res = LineString::new(x[..=k].to_vec()).simplify(&epsilon).0;
+    let tail = LineString::new(x[k..].to_vec()).simplify(&epsilon).0;
+    res.extend(tail.into_iter().skip(1));
+
+    if res.len() < 4 {
+        x.to_vec()
+    } else {
+        res
+    }
+}
+
+#[extendr]
+fn simplify_shared_(x: List, epsilon: f64) -> Robj {
+    if !(x.inherits("rs_POLYGON") || x.inherits("rs_MULTIPOLYGON")) {
+        panic!("`x` must be an `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    if !epsilon.is_finite() || epsilon <= 0.0 {
+        panic!("`epsilon` must be a positive number")
+    }
+
+    let cls = rsgeo_type(&x);
+
+    // the rings of each polygon where the first ring is the exterior
+    let features = geometry_from_list(x)
+        .into_iter()
+        .map(|xi| {
+            let plys = match xi? {
+                Geometry::Polygon(p) => vec![p],
+                Geometry::MultiPolygon(mp) => mp.0,
+                _ => return None,
+            };
+
+            let rings = plys
+                .iter()
+                .map(|p| {
+                    std::iter::once(p.exterior())
+                        .chain(p.interiors())
+                        .map(ring_coords)
+                        .collect::<Vec<Vec<Coord>>>()
+                })
+                .collect::<Vec<Vec<Vec<Coord>>>>();
+
+            Some(rings)
+        })
+        .collect::<Vec<Option<Vec<Vec<Vec<Coord>>>>>>();
+
+    // the distinct coordinates connected to each coordinate. Coordinates
+    // that aren't connected to exactly two others are where boundaries
+    // meet and are kept in place.
+    let mut neighbors: HashMap<Key, HashSet<Key>> = HashMap::new();
+
+    for ring in features.iter().flatten().flatten().flatten() {
+        let n = ring.len();
+
+        for i in 0..n {
+            let (a, b) = (coord_key(&ring[i]), coord_key(&ring[(i + 1) % n]));
+            neighbors.entry(a).or_default().insert(b);
+            neighbors.entry(b).or_default().insert(a);
+        }
+    }
+
+    let is_node = |c: &Coord| !matches!(neighbors.get(&coord_key(c)), Some(nb) if nb.len() == 2);
+
+    // each arc is simplified once in a canonical direction and reused by
+    // every ring that shares it so that neighbors remain edge-matched
+    let mut arc_cache: HashMap<Vec<Key>, Vec<Coord>> = HashMap::new();
+
+    let mut simplify_ring = |ring: &[Coord]| -> Option<LineString> {
+        if ring.len() < 3 {
+            return None;
+        }
+
+        let mut res: Vec<Coord> = Vec::new();
+
+        for arc in ring_arcs(ring, is_node) {
+            let keys = arc.iter().map(coord_key).collect::<Vec<Key>>();
+            let rev_keys = keys.iter().rev().copied().collect::<Vec<Key>>();
+            let reversed = rev_keys < keys;
+            let key = if reversed { rev_keys } else { keys };
+
+            let mut piece = arc_cache
+                .entry(key)
+                .or_insert_with(|| {
+                    let mut canonical = arc.clone();
+                    if reversed {
+                        canonical.reverse();
+                    }
+                    simplify_arc(&canonical, epsilon)
+                })
+                .clone();
+
+            if reversed {
+                piece.reverse();
+            }
+
+            // each arc starts where the previous one ended
+            let skip = usize::from(!res.is_empty());
+            res.extend(piece.into_iter().skip(skip));
+        }
+
+        if res.len() < 4 {
+            None
+        } else {
+            Some(LineString::new(res))
+        }
+    };
+
+    let mut res: Vec<Robj> = Vec::with_capacity(features.len());
+
+    for fi in features {
+        let plys = match fi {
+            Some(plys) => plys,
+            None => {
+                res.push(NULL.into_robj());
+                continue;
+            }
+        };
+
+        let mut simplified: Vec<Polygon> = Vec::with_capacity(plys.len());
+
+        for rings in plys {
+            // polygons whose exterior collapses are dropped
+            let exterior = match simplify_ring(&rings[0]) {
+                Some(ext) => ext,
+                None => continue,
+            };
+
+            let mut interiors: Vec<LineString> = Vec::new();
+
+            for ring in rings[1..].iter() {
+                if let Some(int) = simplify_ring(ring) {
+                    interiors.push(int);
+                }
+            }
+
+            simplified.push(Polygon::new(exterior, interiors));
+        }
+
+        let geom = if simplified.is_empty() {
+            None
+        } else if cls == "polygon" {
+            Some(Geometry::from(simplified.swap_remove(0)))
+        } else {
+            Some(Geometry::from(MultiPolygon::new(simplified)))
+        };
+
+        match geom {
+            Some(g) => res.push(Geom::from(g).into_robj()),
+            None => res.push(NULL.into_robj()),
+        }
+    }
+
+    as_rsgeo_vctr(List::from_values(res), cls.as_str())
+}
+
+// Whether `cur` lies on the segment from `prev` to `next` within `tolerance`.
+// Coordinates beyond either end of the segment are never redundant since
+// removing them would remove a spike.
+fn is_collinear(prev: Coord, cur: Coord, next: Coord, tolerance: f64) -> bool {
+    let d = next - prev;
+    let v = cur - prev;
+    let len2 = d.x * d.x + d.y * d.y;
+
+    if len2 == 0.0 {
+        return v.x.hypot(v.y) <= tolerance;
+    }
+
+    let t = (v.x * d.x + v.y * d.y) / len2;
+
+    if !(0.0..=1.0).contains(&t) {
+        return false;
+    }
+
+    (v.x * d.y - v.y * d.x).abs() / len2.sqrt() <= tolerance
+}
+
+fn cross(a: Coord, b: Coord) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn dot(a: Coord, b: Coord) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+// The directions from `anchor` of the lines that pass within `tolerance` of
+// every coordinate added to the cone without any of them lying behind
+// `anchor`. The directions are counter-clockwise of `lo`, clockwise of `hi`,
+// and within 90 degrees of `front`, the direction of the first coordinate
+// added. Each coordinate narrows the cone in constant time so that long runs
+// of removed coordinates aren't rechecked.
+struct Cone {
+    anchor: Coord,
+    tolerance: f64,
+    // `front`, `lo`, and `hi`
+    bounds: Option<(Coord, Coord, Coord)>,
+    empty: bool,
+    // the distance to the farthest coordinate from `anchor`
+    far: f64,
+}
+
+impl Cone {
+    fn new(anchor: Coord, tolerance: f64) -> Self {
+        Cone {
+            anchor,
+            tolerance,
+            bounds: None,
+            empty: false,
+            far: 0.0,
+        }
+    }
+
+    fn add(&mut self, x: Coord) {
+        let v = x - self.anchor;
+        let r = v.x.hypot(v.y);
+
+        if !r.is_finite() {
+            self.empty = true;
+            return;
+        } else if r == 0.0 {
+            return;
+        }
+
+        self.far = self.far.max(r);
+
+        // Rotates `v` by the largest angle that keeps the line within
+        // `tolerance` of `x`. Coordinates within `tolerance` of `anchor` only
+        // have to be in front of it.
+        let (sin, cos) = if r > self.tolerance {
+            let sin = self.tolerance / r;
+            (sin, (1.0 - sin * sin).sqrt())
+        } else {
+            (1.0, 0.0)
+        };
+
+        let lo = coord! { x: v.x * cos + v.y * sin, y: v.y * cos - v.x * sin };
+        let hi = coord! { x: v.x * cos - v.y * sin, y: v.y * cos + v.x * sin };
+
+        let (front, lo, hi) = match self.bounds {
+            Some((f, l, h)) => (
+                f,
+                if cross(l, lo) > 0.0 { lo } else { l },
+                if cross(hi, h) > 0.0 { hi } else { h },
+            ),
+            None => (v, lo, hi),
+        };
+
+        self.empty |= cross(lo, hi) < 0.0 || dot(front, lo) < 0.0 || dot(front, hi) < 0.0;
+        self.bounds = Some((front, lo, hi));
+    }
+
+    // Whether every coordinate added is within `tolerance` of the line from
+    // `anchor` to `x`. None of them may be farther from `anchor` than `x` so
+    // that they are within `tolerance` of the segment as well.
+    fn contains(&self, x: Coord) -> bool {
+        let d = x - self.anchor;
+
+        if self.empty || !(d.x.is_finite() && d.y.is_finite()) {
+            return false;
+        }
+
+        match self.bounds {
+            None => true,
+            Some(_) if d.x == 0.0 && d.y == 0.0 => self.far <= self.tolerance,
+            Some((front, lo, hi)) => {
+                cross(lo, d) >= 0.0
+                    && cross(d, hi) >= 0.0
+                    && dot(front, d) >= 0.0
+                    && self.far <= d.x.hypot(d.y)
+            }
+        }
+    }
+}
+
+// The indices of the coordinates of a LineString that are kept when collinear
+// coordinates are removed. The start and end are always kept. A coordinate is
+// only removed when it and every coordinate removed since the last kept one
+// are within `tolerance` of the segment from the last kept coordinate to the
+// next coordinate so that small deviations can't accumulate.
+fn collinear_keep(x: &[Coord], tolerance: f64) -> Vec<usize> {
+    let n = x.len();
+
+    if n < 3 {
+        return (0..n).collect();
+    }
+
+    let mut keep = vec![0];
+    let mut cone = Cone::new(x[0], tolerance);
+
+    for i in 1..n - 1 {
+        cone.add(x[i]);
+
+        if !cone.contains(x[i + 1]) {
+            keep.push(i);
+            cone = Cone::new(x[i], tolerance);
+        }
+    }
+
+    keep.push(n - 1);
+    keep
+}
+
+// Removes collinear coordinates from a LineString keeping its start and end.
+fn remove_collinear_line(x: &LineString, tolerance: f64) -> LineString {
+    LineString::new(
+        collinear_keep(&x.0, tolerance)
+            .into_iter()
+            .map(|i| x.0[i])
+            .collect(),
+    )
+}
+
+// Removes collinear coordinates from a ring including its closing coordinate.
+// Rings that would collapse are returned unchanged.
+fn remove_collinear_ring(x: &LineString, tolerance: f64) -> LineString {
+    let crds = &x.0;
+    let keep = collinear_keep(crds, tolerance);
+    let m = keep.len();
+
+    if m < 4 || crds.first() != crds.last() {
+        return x.clone();
+    }
+
+    // the start of the ring is only checked once its neighbors are known.
+    // The coordinates removed on either side of it are checked as well.
+    let (prev, next) = (keep[m - 2], keep[1]);
+    let removable = (prev + 1..crds.len())
+        .chain(0..next)
+        .all(|j| is_collinear(crds[prev], crds[j], crds[next], tolerance));
+
+    let mut res = keep.iter().map(|&i| crds[i]).collect::<Vec<Coord>>();
+
+    if removable {
+        res.pop();
+        res.remove(0);
+        res.push(res[0]);
+    }
+
+    if res.len() < 4 {
+        x.clone()
+    } else {
+        LineString::new(res)
+    }
+}
+
+fn remove_collinear_polygon(x: &Polygon, tolerance: f64) -> Polygon {
+    Polygon::new(
+        remove_collinear_ring(x.exterior(), tolerance),
+        x.interiors()
+            .iter()
+            .map(|r| remove_collinear_ring(r, tolerance))
+            .collect(),
+    )
+}
+
+#[extendr]
+fn remove_collinear_(x: List, tolerance: f64) -> Robj {
+    verify_rsgeo(&x);
+
+    if tolerance.is_nan() || tolerance < 0.0 {
+        panic!("`tolerance` must be a non-negative number")
+    }
+
+    let cls = rsgeo_type(&x);
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let res: Geometry = match xi? {
+                Geometry::LineString(l) => remove_collinear_line(&l, tolerance).into(),
+                Geometry::MultiLineString(ml) => ml
+                    .iter()
+                    .map(|l| remove_collinear_line(l, tolerance))
+                    .collect::<MultiLineString>()
+                    .into(),
+                Geometry::Polygon(p) => remove_collinear_polygon(&p, tolerance).into(),
+                Geometry::MultiPolygon(mp) => mp
+                    .iter()
+                    .map(|p| remove_collinear_polygon(p, tolerance))
+                    .collect::<MultiPolygon>()
+                    .into(),
+                g => g,
+            };
+
+            Some(res)
+        })
+        .collect::<Vec<Option<Geometry>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), cls.as_str())
+}
+
+// the number of times epsilon is halved before giving up on simplification
+const MAX_FALLBACKS: usize = 8;
+
+fn simplify_geometry(x: &Geometry, epsilon: f64) -> Geometry {
+    match x {
+        Geometry::LineString(l) => l.simplify(&epsilon).into(),
+        Geometry::MultiLineString(ml) => ml.simplify(&epsilon).into(),
+        Geometry::Polygon(p) => p.simplify(&epsilon).into(),
+        Geometry::MultiPolygon(mp) => mp.simplify(&epsilon).into(),
+        g => g.clone(),
+    }
+}
+
+// Simplifies with a smaller epsilon until the result is valid. The geometry
+// is returned unchanged when no epsilon results in a valid geometry. The
+// boolean is whether a smaller epsilon was needed.
+fn simplify_valid(x: Geometry, epsilon: f64) -> (Geometry, bool) {
+    let mut eps = epsilon;
+
+    for i in 0..=MAX_FALLBACKS {
+        let res = simplify_geometry(&x, eps);
+
+        if geometry_problem(&res).is_none() {
+            return (res, i > 0);
+        }
+
+        eps /= 2.0;
+    }
+
+    (x, true)
+}
+
+#[extendr]
+fn simplify_safe_(x: List, epsilon: f64) -> Robj {
+    if !epsilon.is_finite() || epsilon <= 0.0 {
+        panic!("`epsilon` must be a positive number")
+    }
+
+    let cls = rsgeo_type(&x);
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| Some(simplify_valid(xi?, epsilon)))
+        .collect::<Vec<Option<(Geometry, bool)>>>();
+
+    let mut fallback: Vec<Rbool> = Vec::with_capacity(res_vec.len());
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some((g, fb)) => {
+                fallback.push(Rbool::from(fb));
+                Geom::from(g).into_robj()
+            }
+            None => {
+                fallback.push(Rbool::na());
+                NULL.into_robj()
+            }
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), cls.as_str())
+        .set_attrib("fallback", Logicals::from_values(fallback))
+        .unwrap()
+}
+
 extendr_module! {
     mod simplification;
     fn simplify_geoms_;
     fn simplify_vw_geoms_;
     fn simplify_vw_preserve_geoms_;
+    fn simplify_to_n_;
+    fn simplify_shared_;
+    fn remove_collinear_;
+    fn simplify_safe_;
 }
@@ -0,0 +1,765 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo::algorithm::triangulate_spade::SpadeTriangulationConfig;
+use geo::{Area, BoundingRect, Contains, Densify, EuclideanLength, TriangulateSpade};
+use geo_types::{
+    coord, Coord, Geometry, Line, LineString, MultiLineString, Point, Polygon, Triangle,
+};
+
+use crate::union::coord_key;
+
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+// the center of the circle passing through each vertex of a triangle
+fn circumcenter(x: &Triangle) -> Option<Coord> {
+    let [a, b, c] = x.to_array();
+
+    // relative to `a` for numerical stability
+    let (bx, by) = (b.x - a.x, b.y - a.y);
+    let (cx, cy) = (c.x - a.x, c.y - a.y);
+    let d = 2.0 * (bx * cy - by * cx);
+
+    if d == 0.0 {
+        return None;
+    }
+
+    let b2 = bx * bx + by * by;
+    let c2 = cx * cx + cy * cy;
+
+    Some(coord! {
+        x: a.x + (cy * b2 - by * c2) / d,
+        y: a.y + (bx * c2 - cx * b2) / d,
+    })
+}
+
+// a node in the Dijkstra search queue ordered by the smallest distance
+struct State {
+    node: usize,
+    distance: f64,
+}
+
+impl PartialEq for State {
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl Eq for State {}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+// shortest distances to each node reachable from `from` and the node
+// preceding each one on its shortest path
+fn dijkstra(adj: &[Vec<(usize, f64)>], from: usize) -> (Vec<f64>, Vec<Option<usize>>) {
+    let mut dist = vec![f64::INFINITY; adj.len()];
+    let mut prev = vec![None; adj.len()];
+    let mut queue = BinaryHeap::new();
+
+    dist[from] = 0.0;
+    queue.push(State {
+        node: from,
+        distance: 0.0,
+    });
+
+    while let Some(State { node, distance }) = queue.pop() {
+        if distance > dist[node] {
+            continue;
+        }
+
+        for &(nb, w) in adj[node].iter() {
+            let d = distance + w;
+            if d < dist[nb] {
+                dist[nb] = d;
+                prev[nb] = Some(node);
+                queue.push(State {
+                    node: nb,
+                    distance: d,
+                });
+            }
+        }
+    }
+
+    (dist, prev)
+}
+
+// the reachable node furthest from `from`
+fn furthest(dist: &[f64]) -> (usize, f64) {
+    dist.iter().enumerate().filter(|(_, d)| d.is_finite()).fold(
+        (0, f64::NEG_INFINITY),
+        |acc, (i, &d)| {
+            if d > acc.1 {
+                (i, d)
+            } else {
+                acc
+            }
+        },
+    )
+}
+
+// Approximates the centerline of a polygon. The boundary is densified and
+// triangulated. Connecting the circumcenters of adjacent triangles gives the
+// Voronoi diagram of the boundary's coordinates and the Voronoi edges that
+// are within the polygon approximate its medial axis. The longest path
+// through the medial axis is used as the centerline.
+fn polygon_centerline(x: &Polygon) -> Option<LineString> {
+    let area = x.unsigned_area();
+    let perimeter = x.exterior().euclidean_length()
+        + x.interiors()
+            .iter()
+            .map(|r| r.euclidean_length())
+            .sum::<f64>();
+
+    if area == 0.0 || !perimeter.is_finite() {
+        return None;
+    }
+
+    // for elongated shapes 2 * area / perimeter is close to the width so the
+    // boundary is densified to a quarter of the width
+    let step = area / (2.0 * perimeter);
+    let config = SpadeTriangulationConfig {
+        snap_radius: step * 1e-3,
+    };
+
+    let triangles = x.densify(step).constrained_triangulation(config).ok()?;
+
+    // the triangles sharing each edge
+    let mut edges: HashMap<((u64, u64), (u64, u64)), Vec<usize>> = HashMap::new();
+    for (i, tri) in triangles.iter().enumerate() {
+        let [a, b, c] = tri.to_array();
+        for (s, e) in [(a, b), (b, c), (c, a)] {
+            let (ks, ke) = (coord_key(&s), coord_key(&e));
+            let key = if ks < ke { (ks, ke) } else { (ke, ks) };
+            edges.entry(key).or_default().push(i);
+        }
+    }
+
+    let centers = triangles
+        .iter()
+        .map(|t| circumcenter(t).filter(|c| x.contains(&Point::from(*c))))
+        .collect::<Vec<Option<Coord>>>();
+
+    // circumcenters are nodes of the graph with identical centers merged
+    let mut nodes: Vec<Coord> = Vec::new();
+    let mut node_index: HashMap<(u64, u64), usize> = HashMap::new();
+    let mut adj: Vec<Vec<(usize, f64)>> = Vec::new();
+
+    let mut node_id = |c: Coord, adj: &mut Vec<Vec<(usize, f64)>>| {
+        *node_index.entry(coord_key(&c)).or_insert_with(|| {
+            nodes.push(c);
+            adj.push(Vec::new());
+            nodes.len() - 1
+        })
+    };
+
+    for tris in edges.values() {
+        if let [i, j] = tris[..] {
+            let (ci, cj) = match (centers[i], centers[j]) {
+                (Some(ci), Some(cj)) => (ci, cj),
+                _ => continue,
+            };
+
+            let (ni, nj) = (node_id(ci, &mut adj), node_id(cj, &mut adj));
+
+            if ni != nj {
+                let w = (ci.x - cj.x).hypot(ci.y - cj.y);
+                adj[ni].push((nj, w));
+                adj[nj].push((ni, w));
+            }
+        }
+    }
+
+    // the longest path in each connected component is found by searching
+    // from an arbitrary node to the furthest node and then from that node
+    let mut visited = vec![false; adj.len()];
+    let mut best: Option<(f64, usize, Vec<Option<usize>>)> = None;
+
+    for start in 0..adj.len() {
+        if visited[start] {
+            continue;
+        }
+
+        let (dist, _) = dijkstra(&adj, start);
+        dist.iter()
+            .enumerate()
+            .filter(|(_, d)| d.is_finite())
+            .for_each(|(i, _)| visited[i] = true);
+
+        let (from, _) = furthest(&dist);
+        let (dist, prev) = dijkstra(&adj, from);
+        let (to, len) = furthest(&dist);
+
+        let is_longer = match &best {
+            Some((b, _, _)) => len > *b,
+            None => true,
+        };
+
+        if is_longer {
+            best = Some((len, to, prev));
+        }
+    }
+
+    let (_, to, prev) = best?;
+
+    let mut crds = vec![nodes[to]];
+    let mut cur = to;
+    while let Some(p) = prev[cur] {
+        crds.push(nodes[p]);
+        cur = p;
+    }
+
+    if crds.len() < 2 {
+        return None;
+    }
+
+    Some(LineString::new(crds))
+}
+
+#[extendr]
+/// Approximate the Centerline of Polygons
+///
+/// Estimates the centerline of elongated polygons such as rivers or road
+/// casings. This is useful for creating labels or network representations
+/// of features that are mapped as areas.
+///
+/// @param x an object of class `rs_POLYGON`
+///
+/// @details
+///
+/// The boundary of each polygon is densified and triangulated. The edges of
+/// the Voronoi diagram of the boundary's coordinates that are inside of the
+/// polygon approximate its medial axis. The longest path through the medial
+/// axis is returned as the centerline.
+///
+/// This is an approximation that works best for long, narrow polygons. Some
+/// limitations to be aware of:
+///
+/// - the ends of the centerline follow a branch of the medial axis into a
+///   corner of the polygon rather than stopping at the middle of its end
+/// - branching shapes such as river confluences return only the longest
+///   branch
+/// - for polygons with holes, the path may go around either side of a hole
+///
+/// The result has many coordinates. Consider simplifying it with
+/// [`simplify_geoms()`].
+///
+/// @export
+/// @returns
+/// An object of class `rs_LINESTRING`. Missing or empty polygons result in a
+/// missing geometry.
+/// @examples
+/// x <- c(0, 10, 20, 20, 10, 0, 0)
+/// y <- c(0, 2, 0, 1, 3, 1, 0)
+/// ply <- geom_polygon(x, y)
+/// centerline(ply)
+fn centerline(x: List) -> Robj {
+    if !x.inherits("rs_POLYGON") {
+        panic!("`x` must be an `rs_POLYGON`")
+    }
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| match xi? {
+            Geometry::Polygon(p) => polygon_centerline(&p),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<Option<LineString>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(l) => Geom::from(l).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "linestring")
+}
+
+// a vertex of the shrinking wavefront used to build a straight skeleton
+struct WaveVertex {
+    pos: Coord,
+    vel: Coord,
+    // where the vertex was created
+    origin: Coord,
+    // unit directions of the edges before and after the vertex
+    in_dir: Coord,
+    out_dir: Coord,
+    prev: usize,
+    next: usize,
+    // the id of the wavefront polygon the vertex belongs to
+    lav: usize,
+    active: bool,
+}
+
+enum WaveEvent {
+    // the edge starting at a vertex shrinks to nothing
+    Edge(usize),
+    // a reflex vertex reaches the edge starting at another vertex
+    Split(usize, usize),
+}
+
+fn dot(a: Coord, b: Coord) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+fn cross(a: Coord, b: Coord) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+// the unit normal pointing to the left of a direction
+fn left_normal(x: Coord) -> Coord {
+    coord! { x: -x.y, y: x.x }
+}
+
+fn distance(a: Coord, b: Coord) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+fn unit(x: Coord) -> Coord {
+    let len = x.x.hypot(x.y);
+    coord! { x: x.x / len, y: x.y / len }
+}
+
+// The velocity of a vertex whose adjacent edges both move inwards at unit
+// speed. There is none when the edges point in opposite directions.
+fn bisector_velocity(in_dir: Coord, out_dir: Coord) -> Option<Coord> {
+    let n1 = left_normal(in_dir);
+    let n2 = left_normal(out_dir);
+    let denom = 1.0 + dot(n1, n2);
+
+    if denom < 1e-12 {
+        None
+    } else {
+        Some((n1 + n2) / denom)
+    }
+}
+
+// The wavefront of a polygon shrinking inwards. The paths traced by its
+// vertices are the arcs of the straight skeleton.
+struct Wavefront {
+    verts: Vec<WaveVertex>,
+    arcs: Vec<Line>,
+    n_lav: usize,
+    eps: f64,
+}
+
+impl Wavefront {
+    // `ring` is counter-clockwise without duplicate or closing coordinates
+    fn new(ring: &[Coord], eps: f64) -> Self {
+        let n = ring.len();
+        let verts = (0..n)
+            .map(|i| {
+                let prev = (i + n - 1) % n;
+                let next = (i + 1) % n;
+                let in_dir = unit(ring[i] - ring[prev]);
+                let out_dir = unit(ring[next] - ring[i]);
+
+                WaveVertex {
+                    pos: ring[i],
+                    vel: bisector_velocity(in_dir, out_dir).unwrap_or_default(),
+                    origin: ring[i],
+                    in_dir,
+                    out_dir,
+                    prev,
+                    next,
+                    lav: 0,
+                    active: true,
+                }
+            })
+            .collect::<Vec<WaveVertex>>();
+
+        let mut res = Self {
+            verts,
+            arcs: Vec::new(),
+            n_lav: 1,
+            eps,
+        };
+
+        // spikes in the input collapse immediately
+        for i in 0..n {
+            res.resolve(i);
+        }
+
+        res
+    }
+
+    fn push(
+        &mut self,
+        pos: Coord,
+        in_dir: Coord,
+        out_dir: Coord,
+        prev: usize,
+        next: usize,
+        lav: usize,
+    ) -> usize {
+        let k = self.verts.len();
+
+        self.verts.push(WaveVertex {
+            pos,
+            vel: bisector_velocity(in_dir, out_dir).unwrap_or_default(),
+            origin: pos,
+            in_dir,
+            out_dir,
+            prev,
+            next,
+            lav,
+            active: true,
+        });
+
+        self.verts[prev].next = k;
+        self.verts[next].prev = k;
+        k
+    }
+
+    // removes a vertex from the wavefront recording the path it traced
+    fn finish(&mut self, k: usize) {
+        let v = &mut self.verts[k];
+        v.active = false;
+        self.arcs.push(Line::new(v.origin, v.pos));
+    }
+
+    fn lav_members(&self, k: usize) -> Vec<usize> {
+        let mut res = vec![k];
+        let mut cur = self.verts[k].next;
+
+        while cur != k && res.len() <= self.verts.len() {
+            res.push(cur);
+            cur = self.verts[cur].next;
+        }
+
+        res
+    }
+
+    // A wavefront with two vertices has no area left. The vertices are
+    // joined and removed.
+    fn close_if_collapsed(&mut self, k: usize) -> bool {
+        let members = self.lav_members(k);
+
+        if members.len() > 2 {
+            return false;
+        }
+
+        if let [a, b] = members[..] {
+            self.arcs
+                .push(Line::new(self.verts[a].pos, self.verts[b].pos));
+        }
+
+        members.into_iter().for_each(|m| self.finish(m));
+        true
+    }
+
+    // A vertex whose edges point in opposite directions is the tip of a
+    // spike with no width. The tip moves instantly to the nearest of its
+    // neighbors and merges with it.
+    fn resolve(&mut self, k: usize) {
+        let mut k = k;
+
+        loop {
+            if !self.verts[k].active || self.close_if_collapsed(k) {
+                return;
+            }
+
+            let v = &self.verts[k];
+            if bisector_velocity(v.in_dir, v.out_dir).is_some() {
+                return;
+            }
+
+            let (p, nx) = (v.prev, v.next);
+            let dp = distance(v.pos, self.verts[p].pos);
+            let dn = distance(v.pos, self.verts[nx].pos);
+            let m = if dp <= dn { p } else { nx };
+
+            let pos = self.verts[m].pos;
+            self.arcs.push(Line::new(self.verts[k].pos, pos));
+            self.finish(k);
+            self.finish(m);
+
+            let vk = &self.verts[k];
+            let (in_dir, out_dir, lav) = (vk.in_dir, vk.out_dir, vk.lav);
+
+            k = if m == p {
+                let (in_dir, prev) = (self.verts[p].in_dir, self.verts[p].prev);
+                self.push(pos, in_dir, out_dir, prev, nx, lav)
+            } else {
+                let (out_dir, next) = (self.verts[nx].out_dir, self.verts[nx].next);
+                self.push(pos, in_dir, out_dir, p, next, lav)
+            };
+        }
+    }
+
+    // the earliest event and the time until it happens
+    fn next_event(&self) -> Option<(f64, WaveEvent)> {
+        let mut best: Option<(f64, WaveEvent)> = None;
+        let active = (0..self.verts.len())
+            .filter(|&i| self.verts[i].active)
+            .collect::<Vec<usize>>();
+
+        for &i in active.iter() {
+            let vi = &self.verts[i];
+            let vj = &self.verts[vi.next];
+            let length = dot(vj.pos - vi.pos, vi.out_dir);
+            let rate = dot(vj.vel - vi.vel, vi.out_dir);
+
+            if rate < -1e-12 {
+                let dt = length.max(0.0) / -rate;
+                if !matches!(&best, Some((b, _)) if dt >= *b) {
+                    best = Some((dt, WaveEvent::Edge(i)));
+                }
+            }
+        }
+
+        for &r in active.iter() {
+            let vr = &self.verts[r];
+
+            // only reflex vertices can split the wavefront
+            if cross(vr.in_dir, vr.out_dir) >= -1e-12 {
+                continue;
+            }
+
+            for &a in active.iter() {
+                let va = &self.verts[a];
+                let vb = &self.verts[va.next];
+
+                if a == r || va.next == r || va.lav != vr.lav {
+                    continue;
+                }
+
+                let n = left_normal(va.out_dir);
+                let offset = dot(vr.pos - va.pos, n);
+                let approach = 1.0 - dot(vr.vel, n);
+
+                if offset <= self.eps || approach <= 1e-12 {
+                    continue;
+                }
+
+                let dt = offset / approach;
+
+                // edge events are preferred when they happen at the same time
+                if matches!(&best, Some((b, _)) if dt >= *b - self.eps) {
+                    continue;
+                }
+
+                // the vertex must reach the edge between its endpoints
+                let hit = vr.pos + vr.vel * dt;
+                let start = va.pos + va.vel * dt;
+                let end = vb.pos + vb.vel * dt;
+                let along = end - start;
+                let len2 = dot(along, along);
+
+                if len2 == 0.0 {
+                    continue;
+                }
+
+                let frac = dot(hit - start, along) / len2;
+                let tol = self.eps / len2.sqrt();
+
+                if (-tol..=1.0 + tol).contains(&frac) {
+                    best = Some((dt, WaveEvent::Split(r, a)));
+                }
+            }
+        }
+
+        best
+    }
+
+    fn advance(&mut self, dt: f64) {
+        for v in self.verts.iter_mut().filter(|v| v.active) {
+            v.pos = v.pos + v.vel * dt;
+        }
+    }
+
+    fn edge_event(&mut self, i: usize) {
+        let j = self.verts[i].next;
+        let pos = (self.verts[i].pos + self.verts[j].pos) / 2.0;
+
+        self.verts[i].pos = pos;
+        self.verts[j].pos = pos;
+        self.finish(i);
+        self.finish(j);
+
+        let (in_dir, prev, lav) = (self.verts[i].in_dir, self.verts[i].prev, self.verts[i].lav);
+        let (out_dir, next) = (self.verts[j].out_dir, self.verts[j].next);
+        let k = self.push(pos, in_dir, out_dir, prev, next, lav);
+        self.resolve(k);
+    }
+
+    // the wavefront is split into two at the point where `r` meets the edge
+    // starting at `a`
+    fn split_event(&mut self, r: usize, a: usize) {
+        let b = self.verts[a].next;
+        let pos = self.verts[r].pos;
+        self.finish(r);
+
+        let (vr, va) = (&self.verts[r], &self.verts[a]);
+        let (in_r, out_r, out_a) = (vr.in_dir, vr.out_dir, va.out_dir);
+        let (prev_r, next_r, lav) = (vr.prev, vr.next, vr.lav);
+
+        let v1 = self.push(pos, in_r, out_a, prev_r, b, lav);
+        let v2 = self.push(pos, out_a, out_r, a, next_r, self.n_lav);
+
+        for m in self.lav_members(v2) {
+            self.verts[m].lav = self.n_lav;
+        }
+        self.n_lav += 1;
+
+        self.resolve(v1);
+        self.resolve(v2);
+    }
+
+    fn run(mut self) -> Option<Vec<Line>> {
+        // each event removes a vertex or splits at a reflex vertex so this
+        // is only reached when the algorithm fails to make progress
+        let max_events = self.verts.len().pow(2) + 16;
+
+        for _ in 0..max_events {
+            match self.next_event() {
+                Some((dt, event)) => {
+                    self.advance(dt);
+                    match event {
+                        WaveEvent::Edge(i) => self.edge_event(i),
+                        WaveEvent::Split(r, a) => self.split_event(r, a),
+                    }
+                }
+                None => {
+                    // remaining vertices are joined to where they stopped
+                    for k in 0..self.verts.len() {
+                        if self.verts[k].active {
+                            self.finish(k);
+                        }
+                    }
+
+                    let eps = self.eps;
+                    self.arcs.retain(|l| l.dx().hypot(l.dy()) > eps);
+                    return Some(self.arcs);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+// The straight skeleton of the exterior ring of a polygon.
+fn polygon_skeleton(x: &Polygon) -> Option<Vec<Line>> {
+    let mut ring = x.exterior().0.clone();
+    ring.dedup();
+
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+
+    if ring.len() < 3 {
+        return None;
+    }
+
+    // the wavefront expects counter-clockwise vertices
+    let twice_area = (0..ring.len())
+        .map(|i| cross(ring[i], ring[(i + 1) % ring.len()]))
+        .sum::<f64>();
+
+    if twice_area == 0.0 || !twice_area.is_finite() {
+        return None;
+    } else if twice_area < 0.0 {
+        ring.reverse();
+    }
+
+    let bbox = x.exterior().bounding_rect()?;
+    let eps = bbox.width().hypot(bbox.height()) * 1e-10;
+
+    Wavefront::new(&ring, eps).run()
+}
+
+#[extendr]
+/// Compute the Straight Skeleton of Polygons
+///
+/// The straight skeleton is traced by the vertices of a polygon as its edges
+/// move inwards at the same speed, like the ridges and valleys of a hipped
+/// roof. It is used for roof modeling, generating setbacks, and placing
+/// labels along the spine of a polygon.
+///
+/// @param x an object of class `rs_POLYGON` or `rs_MULTIPOLYGON`
+///
+/// @details
+///
+/// The skeleton is computed with an event-driven wavefront algorithm. The
+/// boundary shrinks until an edge collapses to a point (an edge event) or a
+/// reflex vertex reaches another edge and splits the polygon in two (a split
+/// event). The paths traced by the vertices of the wavefront are the arcs of
+/// the skeleton.
+///
+/// The algorithm supports convex and simple concave polygons. The following
+/// are not supported:
+///
+/// - holes: only the exterior ring of each polygon is used
+/// - vertex events: when two reflex vertices meet at the same time the
+///   result may be incorrect
+///
+/// The algorithm takes time proportional to the cube of the number of
+/// vertices in the worst case so it is best suited to simple shapes such as
+/// building footprints. When it fails to complete, the result is missing.
+///
+/// @export
+/// @returns
+/// An object of class `rs_MULTILINESTRING` where each LineString is an arc
+/// of the skeleton.
+/// @examples
+/// ply <- geom_polygon(c(0, 4, 4, 2, 2, 0, 0), c(0, 0, 2, 2, 4, 4, 0))
+/// straight_skeleton(ply)
+fn straight_skeleton(x: List) -> Robj {
+    if !(x.inherits("rs_POLYGON") || x.inherits("rs_MULTIPOLYGON")) {
+        panic!("`x` must be an `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let plys = match xi? {
+                Geometry::Polygon(p) => vec![p],
+                Geometry::MultiPolygon(p) => p.0,
+                _ => unreachable!(),
+            };
+
+            let arcs = plys
+                .iter()
+                .map(polygon_skeleton)
+                .collect::<Option<Vec<Vec<Line>>>>()?;
+
+            let lns = arcs
+                .into_iter()
+                .flatten()
+                .map(LineString::from)
+                .collect::<Vec<LineString>>();
+
+            Some(MultiLineString::new(lns))
+        })
+        .collect::<Vec<Option<MultiLineString>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(l) => Geom::from(l).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "multilinestring")
+}
+
+extendr_module! {
+    mod skeleton;
+    fn centerline;
+    fn straight_skeleton;
+}
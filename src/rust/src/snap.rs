@@ -0,0 +1,131 @@
+use extendr_api::prelude::*;
+use sfconversions::{
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, rsgeo_type, verify_rsgeo},
+    Geom,
+};
+
+use geo::{Closest, ClosestPoint, CoordsIter, MapCoords};
+use geo_types::{Coord, Geometry, Line, Point};
+
+use crate::utils::geometry_lines;
+use rayon::prelude::*;
+
+// Snaps a coordinate to the closest vertex of the reference geometry if one
+// is within the tolerance. Otherwise it is snapped to the closest point on an
+// edge of the reference geometry if that is within the tolerance.
+fn snap_coord(x: Coord, vertices: &[Coord], edges: &[Line], tolerance: f64) -> Coord {
+    let dist = |a: Coord, b: Coord| (a.x - b.x).hypot(a.y - b.y);
+
+    let closest_vertex = vertices
+        .iter()
+        .map(|v| (*v, dist(x, *v)))
+        .filter(|(_, d)| *d <= tolerance)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+
+    if let Some((v, _)) = closest_vertex {
+        return v;
+    }
+
+    let pnt = Point::from(x);
+
+    let closest_edge = edges
+        .iter()
+        .filter_map(|l| match l.closest_point(&pnt) {
+            Closest::SinglePoint(p) | Closest::Intersection(p) => Some(p.0),
+            Closest::Indeterminate => None,
+        })
+        .map(|c| (c, dist(x, c)))
+        .filter(|(_, d)| *d <= tolerance)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+
+    match closest_edge {
+        Some((c, _)) => c,
+        None => x,
+    }
+}
+
+fn snap_geometry(x: Geometry, reference: &Geometry, tolerance: f64) -> Geometry {
+    let vertices = reference.coords_iter().collect::<Vec<Coord>>();
+    let edges = geometry_lines(reference);
+
+    x.map_coords(|c| snap_coord(c, &vertices, &edges, tolerance))
+}
+
+#[extendr]
+/// Snap Geometries to a Reference
+///
+/// Moves the vertices of each geometry in `x` onto the corresponding
+/// geometry in `reference` when they are within `tolerance`. This is useful for
+/// cleaning up nearly coincident boundaries prior to a union so that slivers
+/// are not created.
+///
+/// @param x an object of class `rsgeo`
+/// @param reference an object of class `rsgeo` of length 1 or the same length
+///   as `x`.
+/// @param tolerance the maximum distance a vertex can be moved.
+///
+/// @details
+///
+/// Each vertex is snapped to the closest vertex of the reference geometry
+/// within the tolerance. If there is no vertex within the tolerance, the
+/// vertex is snapped to the closest point on an edge of the reference
+/// geometry instead. Vertices are moved but never added or removed.
+///
+/// @export
+/// @returns
+/// An object with the same class as `x`.
+/// @examples
+/// x <- geom_linestring(c(0, 5, 10), c(0.05, 0.02, -0.01))
+/// reference <- geom_linestring(c(0, 10), c(0, 0))
+/// snap(x, reference, 0.1)
+fn snap(x: List, reference: List, tolerance: f64) -> Robj {
+    verify_rsgeo(&x);
+    verify_rsgeo(&reference);
+
+    if tolerance.is_nan() || tolerance < 0.0 {
+        panic!("`tolerance` must be a non-negative number")
+    }
+
+    let n_x = x.len();
+    let n_r = reference.len();
+
+    if (n_x != n_r) && (n_r != 1) {
+        panic!("`reference` must be the same length as `x` or length 1")
+    }
+
+    // the output must be the same type as the input
+    let cls = rsgeo_type(&x);
+
+    let reference = geometry_from_list(reference);
+    let reference = if n_r == 1 {
+        vec![reference[0].clone(); n_x]
+    } else {
+        reference
+    };
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .zip(reference.into_par_iter())
+        .map(|(xi, ri)| match (xi, ri) {
+            (Some(xi), Some(ri)) => Some(snap_geometry(xi, &ri, tolerance)),
+            (Some(xi), None) => Some(xi),
+            _ => None,
+        })
+        .collect::<Vec<Option<Geometry>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), cls.as_str())
+}
+
+extendr_module! {
+    mod snap;
+    fn snap;
+}
@@ -1,9 +1,13 @@
 use extendr_api::prelude::*;
 
 // use geo_types::Point;
+use rayon::prelude::*;
 use rstar::primitives::{CachedEnvelope, GeomWithData};
-use rstar::RTree;
-use sfconversions::{Geom, IntoGeom};
+use rstar::{RTree, RTreeObject};
+use sfconversions::{geometry_from_list, vctrs::verify_rsgeo, Geom, IntoGeom};
+
+use geo::{BoundingRect, Centroid};
+use geo_types::{Coord, MultiPoint, Point};
 
 // use cached envelopes
 pub fn create_cached_rtree(geoms: List) -> RTree<GeomWithData<CachedEnvelope<Geom>, usize>> {
@@ -15,6 +19,10 @@ pub fn create_cached_rtree(geoms: List) -> RTree<GeomWithData<CachedEnvelope<Geo
                 None
             } else {
                 let geo = Geom::try_from(xi).unwrap();
+
+                // empty geometries have no envelope
+                geo.geom.bounding_rect()?;
+
                 let env = geo.cached_envelope();
                 Some(GeomWithData::new(env, i))
             }
@@ -24,8 +32,134 @@ pub fn create_cached_rtree(geoms: List) -> RTree<GeomWithData<CachedEnvelope<Geo
     RTree::bulk_load(all_geoms.to_vec())
 }
 
+// Pairs of indices `(i, j)` where `i < j` of the geometries whose envelopes
+// intersect. Pairs are sorted by `i` then `j`. Missing geometries are never
+// paired.
+pub fn envelope_pairs(geoms: List) -> Vec<(usize, usize)> {
+    let tree = create_cached_rtree(geoms);
+    let items = tree.iter().collect::<Vec<_>>();
+
+    let mut pairs = items
+        .par_iter()
+        .flat_map(|a| {
+            let env = a.envelope();
+            tree.locate_in_envelope_intersecting(&env)
+                .filter(|b| b.data > a.data)
+                .map(|b| (a.data, b.data))
+                .collect::<Vec<(usize, usize)>>()
+        })
+        .collect::<Vec<(usize, usize)>>();
+
+    pairs.par_sort_unstable();
+    pairs
+}
+
+// The distance along a Hilbert curve filling a 2^16 by 2^16 grid
+fn hilbert_index(x: u64, y: u64) -> u64 {
+    let n: u64 = 1 << 16;
+    let (mut x, mut y) = (x, y);
+    let mut d = 0;
+    let mut s = n / 2;
+
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        d += s * s * ((3 * rx) ^ ry);
+
+        // rotate the quadrant so the curve is continuous
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
+    }
+
+    d
+}
+
+#[extendr]
+/// Order Geometries Along a Hilbert Curve
+///
+/// Finds the order of geometries along a Hilbert space-filling curve through
+/// their centroids. Geometries that are close to each other in space are
+/// close to each other in this order. Sorting by it improves the locality of
+/// geometries when they are stored or bulk loaded into a spatial index, as
+/// recommended by GeoParquet.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @details
+///
+/// The bounding box of the centroids is divided into a grid of 2^16 by 2^16
+/// cells and each centroid is assigned the position of its cell along the
+/// Hilbert curve. Ties keep their original order.
+///
+/// @export
+/// @returns
+/// An integer vector permutation of `seq_along(x)` that sorts `x` along the
+/// curve. Use it to subset `x` e.g. `x[hilbert_order(x)]`. Missing and empty
+/// geometries are placed last.
+/// @examples
+/// set.seed(0)
+/// x <- geom_point(runif(10), runif(10))
+/// x[hilbert_order(x)]
+fn hilbert_order(x: List) -> Integers {
+    verify_rsgeo(&x);
+
+    let centroids = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| xi?.centroid().map(|p| p.0))
+        .collect::<Vec<Option<Coord>>>();
+
+    let extent = MultiPoint::new(
+        centroids
+            .iter()
+            .flatten()
+            .map(|c| Point::from(*c))
+            .collect(),
+    )
+    .bounding_rect();
+
+    let keys = centroids
+        .iter()
+        .map(|ci| {
+            let (ci, bbox) = match (ci, extent) {
+                (Some(ci), Some(bbox)) => (ci, bbox),
+                _ => return u64::MAX,
+            };
+
+            let max_cell = ((1_u64 << 16) - 1) as f64;
+            let scale = |v: f64, min: f64, size: f64| {
+                if size == 0.0 {
+                    0
+                } else {
+                    ((v - min) / size * max_cell).round() as u64
+                }
+            };
+
+            hilbert_index(
+                scale(ci.x, bbox.min().x, bbox.width()),
+                scale(ci.y, bbox.min().y, bbox.height()),
+            )
+        })
+        .collect::<Vec<u64>>();
+
+    let mut order = (0..keys.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|&i| keys[i]);
+
+    order
+        .into_iter()
+        .map(|i| Rint::from(i as i32 + 1))
+        .collect::<Integers>()
+}
+
 extendr_module! {
     mod spatial_index;
+    fn hilbert_order;
 }
 
 // use std::rc::Rc;
@@ -1,7 +1,10 @@
 use extendr_api::prelude::*;
-use geo::{Contains, Intersects, Within};
+use geo::coordinate_position::CoordPos;
+use geo::dimensions::Dimensions;
+use geo::relate::IntersectionMatrix;
+use geo::{BoundingRect, Contains, Intersects, Relate, Within};
 
-use crate::spatial_index::create_cached_rtree;
+use crate::spatial_index::{create_cached_rtree, envelope_pairs};
 use rstar::RTreeObject;
 use sfconversions::{geometry_from_list, Geom};
 
@@ -57,7 +60,8 @@ fn intersects_sparse(x: List, y: List) -> List {
     let index = Mutex::new(vec![Vec::with_capacity(n); n]);
 
     y.into_par_iter().enumerate().for_each(|(i, yi)| {
-        if let Some(yi) = yi {
+        // empty geometries have no envelope and intersect nothing
+        if let Some(yi) = yi.filter(|g| g.bounding_rect().is_some()) {
             let yi = Geom::from(yi);
             let env = yi.envelope();
             let cands = xtree.locate_in_envelope_intersecting(&env);
@@ -95,7 +99,8 @@ fn contains_sparse(x: List, y: List) -> List {
     let index = Mutex::new(vec![Vec::with_capacity(n); n]);
 
     y.into_par_iter().enumerate().for_each(|(i, yi)| {
-        if let Some(yi) = yi {
+        // empty geometries have no envelope and intersect nothing
+        if let Some(yi) = yi.filter(|g| g.bounding_rect().is_some()) {
             let yi = Geom::from(yi);
             let env = yi.envelope();
             let cands = xtree.locate_in_envelope_intersecting(&env);
@@ -131,7 +136,8 @@ fn within_sparse(x: List, y: List) -> List {
     let index = Mutex::new(vec![Vec::with_capacity(n); n]);
 
     y.into_par_iter().enumerate().for_each(|(i, yi)| {
-        if let Some(yi) = yi {
+        // empty geometries have no envelope and intersect nothing
+        if let Some(yi) = yi.filter(|g| g.bounding_rect().is_some()) {
             let yi = Geom::from(yi);
             let env = yi.envelope();
             let cands = xtree.locate_in_envelope_intersecting(&env);
@@ -259,6 +265,173 @@ fn within_pairwise(x: List, y: List) -> Logicals {
 // 1: a shared line
 // 3: an area intersection
 
+// tests an intersection matrix against a DE-9IM pattern where each
+// character is `T` (non-empty), `F` (empty), or `*` (anything)
+fn matches_pattern(im: &IntersectionMatrix, pattern: &str) -> bool {
+    let positions = [CoordPos::Inside, CoordPos::OnBoundary, CoordPos::Outside];
+
+    positions
+        .iter()
+        .flat_map(|lhs| positions.iter().map(move |rhs| (*lhs, *rhs)))
+        .zip(pattern.chars())
+        .all(|((lhs, rhs), p)| match p {
+            'T' => im.get(lhs, rhs) != Dimensions::Empty,
+            'F' => im.get(lhs, rhs) == Dimensions::Empty,
+            _ => true,
+        })
+}
+
+fn relate_pairwise(x: List, y: List, pattern: &str) -> Logicals {
+    if !x.inherits("rsgeo") || !y.inherits("rsgeo") {
+        panic!("`x` and `y` must be of class `rsgeo`")
+    }
+
+    let x = geometry_from_list(x);
+    let y = geometry_from_list(y);
+
+    let res = x
+        .into_par_iter()
+        .zip(y.into_par_iter())
+        .map(|(xi, yi)| {
+            let im = xi?.relate(&yi?);
+            Some(matches_pattern(&im, pattern))
+        })
+        .collect::<Vec<Option<bool>>>();
+
+    Logicals::from_values(res)
+}
+
+#[extendr]
+/// Boundary-Sensitive Predicates
+///
+/// Pairwise predicates that distinguish between the interior and boundary of
+/// a geometry. These avoid surprising results in spatial joins where a point
+/// that falls exactly on a polygon's boundary should be treated specially.
+///
+/// @param x an object of class `rsgeo`
+/// @param y an object of class `rsgeo`
+///
+/// @details
+///
+/// Predicates are evaluated using the DE-9IM intersection matrix of `x` and `y`.
+/// Each pattern lists the dimension of the intersections between the interior,
+/// boundary, and exterior of `x` and the interior, boundary, and exterior of
+/// `y` where `T` is non-empty, `F` is empty, and `*` is anything.
+///
+/// - `contains_properly_pairwise()` uses `T**FF*FF*`. `y` must be entirely
+///   within the interior of `x` and may not touch its boundary.
+/// - `on_boundary_pairwise()` uses `FF*T**FF*`. `y` must lie entirely on the
+///   boundary of `x`.
+///
+/// Note that `contains_pairwise()` is true when `y` touches the boundary of
+/// `x` as long as some of `y` is in the interior of `x`. A point on the
+/// boundary of a polygon is neither contained nor properly contained by it.
+///
+/// @export
+/// @rdname boundary_predicates
+/// @returns
+/// A logical vector. Comparisons with missing geometries are `NA`.
+/// @examples
+/// ply <- geom_polygon(c(0, 1, 1, 0, 0), c(0, 0, 1, 1, 0))
+/// pnts <- geom_point(c(0.5, 1, 2), c(0.5, 0.5, 0.5))
+/// contains_properly_pairwise(rep(ply, 3), pnts)
+/// on_boundary_pairwise(rep(ply, 3), pnts)
+fn contains_properly_pairwise(x: List, y: List) -> Logicals {
+    relate_pairwise(x, y, "T**FF*FF*")
+}
+
+#[extendr]
+/// @export
+/// @rdname boundary_predicates
+fn on_boundary_pairwise(x: List, y: List) -> Logicals {
+    relate_pairwise(x, y, "FF*T**FF*")
+}
+
+// find the root of `i` compressing the path along the way
+pub fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+#[extendr]
+/// Cluster Intersecting Geometries
+///
+/// Assigns each geometry a cluster identifier such that any two geometries
+/// that intersect share a cluster. Clusters are transitive: if `a` intersects
+/// `b` and `b` intersects `c` then all three share a cluster even if `a` and
+/// `c` do not intersect. This is useful for grouping touching parcels or
+/// overlapping building footprints.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @details
+///
+/// Candidate pairs are found using [`bbox_overlaps_matrix()`] and only those
+/// are tested using the intersects predicate. Clusters are the connected
+/// components of the intersecting pairs.
+///
+/// @export
+/// @returns
+/// An integer vector of cluster identifiers the same length as `x`. Clusters
+/// are numbered in the order they first appear in `x`. Missing geometries are
+/// `NA`.
+/// @examples
+/// lns <- geom_linestring(
+///   c(0, 2, 2, 4, 10, 11, 4, 5),
+///   c(0, 2, 2, 0, 10, 11, 0, 0),
+///   rep(1:4, each = 2)
+/// )
+/// cluster_intersecting(lns)
+fn cluster_intersecting(x: List) -> Integers {
+    if !x.inherits("rsgeo") {
+        panic!("`x` must be of class `rsgeo`")
+    }
+
+    let geoms = geometry_from_list(x.clone());
+    let n = geoms.len();
+
+    let edges = envelope_pairs(x)
+        .into_par_iter()
+        .filter(|(i, j)| match (&geoms[*i], &geoms[*j]) {
+            (Some(gi), Some(gj)) => gi.intersects(gj),
+            _ => false,
+        })
+        .collect::<Vec<(usize, usize)>>();
+
+    let mut parent = (0..n).collect::<Vec<usize>>();
+
+    for (i, j) in edges {
+        let (ri, rj) = (find_root(&mut parent, i), find_root(&mut parent, j));
+        if ri != rj {
+            parent[ri.max(rj)] = ri.min(rj);
+        }
+    }
+
+    // number clusters in the order they first appear
+    let mut ids = vec![0_i32; n];
+    let mut n_clusters = 0_i32;
+
+    (0..n)
+        .map(|i| {
+            if geoms[i].is_none() {
+                return Rint::na();
+            }
+
+            let root = find_root(&mut parent, i);
+
+            if ids[root] == 0 {
+                n_clusters += 1;
+                ids[root] = n_clusters;
+            }
+
+            Rint::from(ids[root])
+        })
+        .collect::<Integers>()
+}
+
 extendr_module! {
     mod topology;
     fn intersects_sparse;
@@ -267,6 +440,9 @@ extendr_module! {
     fn contains_pairwise;
     fn within_sparse;
     fn within_pairwise;
+    fn contains_properly_pairwise;
+    fn on_boundary_pairwise;
+    fn cluster_intersecting;
 }
 
 // This approach is generally slow it works by building two R* trees.
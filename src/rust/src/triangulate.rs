@@ -0,0 +1,313 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::as_rsgeo_vctr, Geom};
+
+use geo::TriangulateSpade;
+use geo_types::{coord, Coord, Geometry, LineString, MultiPolygon, Polygon};
+
+use crate::topology::find_root;
+use rayon::prelude::*;
+use rstar::RTree;
+use spade::{DelaunayTriangulation, Point2, Triangulation};
+
+// The Delaunay triangulation of a point set. Duplicate points are merged
+// so `coords` contains each distinct point once and `edges` are pairs of
+// indices into `coords` with the smaller index first.
+pub struct Delaunay {
+    pub coords: Vec<Coord>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+pub fn delaunay(x: List) -> Delaunay {
+    if !x.inherits("rs_POINT") {
+        panic!("`x` must be an `rs_POINT`")
+    }
+
+    let vertices = geometry_from_list(x)
+        .into_iter()
+        .flatten()
+        .filter_map(|g| match g {
+            Geometry::Point(p) if p.x().is_finite() && p.y().is_finite() => {
+                Some(Point2::new(p.x(), p.y()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<Point2<f64>>>();
+
+    let tri: DelaunayTriangulation<Point2<f64>> = match DelaunayTriangulation::bulk_load(vertices) {
+        Ok(tri) => tri,
+        Err(_) => panic!("`x` cannot be triangulated"),
+    };
+
+    let coords = tri
+        .vertices()
+        .map(|v| {
+            let p = v.position();
+            coord! { x: p.x, y: p.y }
+        })
+        .collect::<Vec<Coord>>();
+
+    let mut edges = tri
+        .undirected_edges()
+        .map(|e| {
+            let [a, b] = e.vertices();
+            let (a, b) = (a.fix().index(), b.fix().index());
+            (a.min(b), a.max(b))
+        })
+        .collect::<Vec<(usize, usize)>>();
+
+    edges.sort_unstable();
+
+    Delaunay { coords, edges }
+}
+
+// converts pairs of coordinate indices into an `rs_LINESTRING`
+pub fn edges_to_linestrings(coords: &[Coord], edges: &[(usize, usize)]) -> Robj {
+    let res = edges
+        .iter()
+        .map(|&(a, b)| Geom::from(LineString::new(vec![coords[a], coords[b]])).into_robj())
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "linestring")
+}
+
+#[extendr]
+/// Constrained Delaunay Triangulation
+///
+/// Triangulates polygons such that the edges of the triangles never cross
+/// the boundary of the polygon or its holes. Delaunay triangles maximize
+/// their smallest angle which avoids the sliver triangles produced by ear
+/// clipping. This makes them better suited for meshes used in finite element
+/// analysis and rendering.
+///
+/// @param x an object of class `rs_POLYGON` or `rs_MULTIPOLYGON`
+///
+/// @details
+///
+/// The triangulation is computed using the `spade` crate. Every edge of the
+/// polygon is an edge of the triangulation and only triangles inside of the
+/// polygon are returned. Coordinates that are very close to each other or to
+/// an edge are snapped together.
+///
+/// When a polygon cannot be triangulated, for example because its rings
+/// intersect, the result is missing.
+///
+/// @export
+/// @returns
+/// An object of class `rs_MULTIPOLYGON` where each polygon is a triangle.
+/// @examples
+/// x <- c(0, 10, 10, 0, 0, 4, 6, 6, 4, 4)
+/// y <- c(0, 0, 10, 10, 0, 4, 4, 6, 6, 4)
+/// ply <- geom_polygon(x, y, ring = rep(1:2, each = 5))
+/// triangulate_constrained(ply)
+fn triangulate_constrained(x: List) -> Robj {
+    if !(x.inherits("rs_POLYGON") || x.inherits("rs_MULTIPOLYGON")) {
+        panic!("`x` must be an `rs_POLYGON` or `rs_MULTIPOLYGON`")
+    }
+
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| {
+            let triangles = match xi? {
+                Geometry::Polygon(p) => p.constrained_triangulation(Default::default()),
+                Geometry::MultiPolygon(p) => p.constrained_triangulation(Default::default()),
+                _ => unreachable!(),
+            };
+
+            let res = triangles
+                .ok()?
+                .into_iter()
+                .map(|t| t.to_polygon())
+                .collect::<Vec<Polygon>>();
+
+            Some(MultiPolygon::new(res))
+        })
+        .collect::<Vec<Option<MultiPolygon>>>();
+
+    let res = res_vec
+        .into_iter()
+        .map(|xi| match xi {
+            Some(p) => Geom::from(p).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), "multipolygon")
+}
+
+fn dist2(a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)
+}
+
+// Keeps the edges of a triangulation whose endpoints satisfy `keep`. `keep`
+// is called with an R*-tree of all points and the two endpoints of the edge.
+fn filter_edges<F>(tri: &Delaunay, keep: F) -> Vec<(usize, usize)>
+where
+    F: Fn(&RTree<[f64; 2]>, [f64; 2], [f64; 2]) -> bool + Sync,
+{
+    let pnts = tri
+        .coords
+        .iter()
+        .map(|c| [c.x, c.y])
+        .collect::<Vec<[f64; 2]>>();
+    let tree = RTree::bulk_load(pnts.clone());
+
+    tri.edges
+        .par_iter()
+        .filter(|(a, b)| keep(&tree, pnts[*a], pnts[*b]))
+        .copied()
+        .collect()
+}
+
+#[extendr]
+/// Delaunay Triangulation Edges
+///
+/// Finds the edges of the Delaunay triangulation of a set of points. The
+/// edges connect each point to its natural neighbors and form the basis of
+/// proximity graphs such as the Gabriel graph and the minimum spanning tree.
+///
+/// @param x an object of class `rs_POINT`
+///
+/// @details
+///
+/// All points in `x` are triangulated together. Missing points are ignored
+/// and duplicate points are treated as a single point. Each edge is returned
+/// once.
+///
+/// @export
+/// @returns
+/// An object of class `rs_LINESTRING` where each LineString is an edge of the
+/// triangulation. The result is empty when there are fewer than 2 distinct
+/// points.
+/// @examples
+/// pnts <- geom_point(c(0, 1, 0, 1, 0.5), c(0, 0, 1, 1, 0.5))
+/// delaunay_edges(pnts)
+fn delaunay_edges(x: List) -> Robj {
+    let tri = delaunay(x);
+    edges_to_linestrings(&tri.coords, &tri.edges)
+}
+
+#[extendr]
+/// Proximity Graphs
+///
+/// Builds proximity graphs connecting a set of points. Both graphs are
+/// subgraphs of the Delaunay triangulation and are used to describe the
+/// structure of spatial networks and point patterns.
+///
+/// @param x an object of class `rs_POINT`
+///
+/// @details
+///
+/// Each edge of the Delaunay triangulation is kept when:
+///
+/// - `gabriel_graph()`: no other point lies inside the circle whose diameter
+///   is the edge.
+/// - `relative_neighborhood_graph()`: no other point is closer to both ends of
+///   the edge than they are to each other.
+///
+/// Points on the boundary of these regions do not remove an edge. The
+/// relative neighborhood graph is a subgraph of the Gabriel graph. Missing
+/// points are ignored and duplicate points are treated as a single point.
+///
+/// @export
+/// @rdname proximity_graphs
+/// @returns
+/// An object of class `rs_LINESTRING` where each LineString is an edge of the
+/// graph.
+/// @examples
+/// pnts <- geom_point(c(0, 2, 1, 1), c(0, 0, 0.5, 3))
+/// gabriel_graph(pnts)
+/// relative_neighborhood_graph(pnts)
+fn gabriel_graph(x: List) -> Robj {
+    let tri = delaunay(x);
+
+    let edges = filter_edges(&tri, |tree, a, b| {
+        let mid = [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0];
+        let r2 = dist2(a, b) / 4.0;
+
+        !tree
+            .locate_within_distance(mid, r2)
+            .any(|c| *c != a && *c != b && dist2(*c, mid) < r2)
+    });
+
+    edges_to_linestrings(&tri.coords, &edges)
+}
+
+#[extendr]
+/// @export
+/// @rdname proximity_graphs
+fn relative_neighborhood_graph(x: List) -> Robj {
+    let tri = delaunay(x);
+
+    let edges = filter_edges(&tri, |tree, a, b| {
+        let d2 = dist2(a, b);
+
+        !tree
+            .locate_within_distance(a, d2)
+            .any(|c| *c != a && *c != b && dist2(*c, a) < d2 && dist2(*c, b) < d2)
+    });
+
+    edges_to_linestrings(&tri.coords, &edges)
+}
+
+#[extendr]
+/// Euclidean Minimum Spanning Tree
+///
+/// Connects a set of points using the edges with the smallest total length.
+/// The minimum spanning tree is useful for planning corridors that connect
+/// every location and for single-linkage clustering.
+///
+/// @param x an object of class `rs_POINT`
+///
+/// @details
+///
+/// The tree is a subgraph of the Delaunay triangulation so only its edges
+/// are considered. Edges are added from shortest to longest when they connect
+/// two parts of the tree that are not already connected (Kruskal's
+/// algorithm). Missing points are ignored and duplicate points are treated as
+/// a single point.
+///
+/// @export
+/// @returns
+/// An object of class `rs_LINESTRING` where each LineString is an edge of the
+/// tree. The `"total_length"` attribute is the sum of the length of every edge.
+/// @examples
+/// pnts <- geom_point(c(0, 1, 3, 3, 0), c(0, 0, 0, 2, 4))
+/// mst <- euclidean_mst(pnts)
+/// mst
+/// attr(mst, "total_length")
+fn euclidean_mst(x: List) -> Robj {
+    let tri = delaunay(x);
+    let crds = &tri.coords;
+
+    let len = |&(a, b): &(usize, usize)| (crds[a].x - crds[b].x).hypot(crds[a].y - crds[b].y);
+
+    let mut candidates = tri.edges.clone();
+    candidates.sort_by(|e1, e2| len(e1).total_cmp(&len(e2)).then(e1.cmp(e2)));
+
+    let mut parent = (0..crds.len()).collect::<Vec<usize>>();
+    let mut edges: Vec<(usize, usize)> = Vec::with_capacity(crds.len().saturating_sub(1));
+
+    for (a, b) in candidates {
+        let (ra, rb) = (find_root(&mut parent, a), find_root(&mut parent, b));
+
+        if ra != rb {
+            parent[ra.max(rb)] = ra.min(rb);
+            edges.push((a, b));
+        }
+    }
+
+    let total_length = edges.iter().map(len).sum::<f64>();
+
+    edges_to_linestrings(crds, &edges)
+        .set_attrib("total_length", total_length)
+        .unwrap()
+}
+
+extendr_module! {
+    mod triangulate;
+    fn triangulate_constrained;
+    fn delaunay_edges;
+    fn gabriel_graph;
+    fn relative_neighborhood_graph;
+    fn euclidean_mst;
+}
@@ -1,21 +1,29 @@
 use extendr_api::prelude::*;
+use geo::line_intersection::{line_intersection, LineIntersection};
 use geo::{
     BooleanOps, LineString, MultiLineString, MultiPoint, MultiPolygon, RemoveRepeatedPoints,
 };
-use geo_types::Point;
-use sfconversions::vctrs::as_rsgeo_vctr;
+use geo_types::{Coord, Geometry, GeometryCollection, Line, Point, Polygon};
+use sfconversions::{
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, verify_rsgeo},
+};
 
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::spatial_index::create_cached_rtree;
 use sfconversions::{Geom, IntoGeom};
 
-use rstar::primitives::{CachedEnvelope, GeomWithData};
-use rstar::{ParentNode, RTreeNode};
+use rayon::prelude::*;
+use rstar::primitives::{CachedEnvelope, GeomWithData, Line as RLine};
+use rstar::{ParentNode, RTree, RTreeNode, RTreeObject};
 
 extendr_module! {
     mod union;
     fn union_geoms;
+    fn unary_union;
 }
 
 #[extendr]
@@ -166,6 +174,280 @@ fn inner(
         })
 }
 
+#[extendr]
+/// Unary Union
+///
+/// Dissolves an entire vector of geometries of any type into a single
+/// geometry, like PostGIS' `ST_UnaryUnion`.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @details
+///
+/// Geometries are grouped by their dimension and each group is dissolved
+/// separately:
+///
+/// - points and multipoints are combined and duplicate points are removed
+/// - linestrings and multilinestrings are noded at their intersections,
+///   overlapping segments are removed, and the result is merged into the
+///   fewest linestrings possible
+/// - polygons and multipolygons are dissolved into a single multipolygon
+///
+/// Missing geometries are ignored.
+///
+/// @export
+/// @returns
+/// An object of class `rsgeo` of length one. When only one group is present
+/// the result is an `rs_MULTIPOINT`, `rs_MULTILINESTRING`, or `rs_MULTIPOLYGON`.
+/// Otherwise an `rs_GEOMETRYCOLLECTION` containing the dissolved groups is
+/// returned.
+/// @examples
+/// lns <- geom_linestring(c(0, 10, 5, 5), c(0, 0, -5, 5), c(1, 1, 2, 2))
+/// unary_union(lns)
+///
+/// x <- c(0, 1, 1, 0, 0)
+/// y <- c(0, 0, 1, 1, 0)
+/// plys <- geom_polygon(c(x, x + 0.5), c(y, y), rep(1:2, each = 5))
+/// unary_union(plys)
+fn unary_union(x: List) -> Robj {
+    verify_rsgeo(&x);
+
+    let mut pnts: Vec<Coord> = Vec::new();
+    let mut lns: Vec<Line> = Vec::new();
+    let mut plys: Vec<Polygon> = Vec::new();
+
+    geometry_from_list(x)
+        .into_iter()
+        .flatten()
+        .for_each(|g| collect_parts(g, &mut pnts, &mut lns, &mut plys));
+
+    let mut res: Vec<Geometry> = Vec::with_capacity(3);
+
+    if !pnts.is_empty() {
+        pnts.sort_by(cmp_coords);
+        pnts.dedup();
+        res.push(Geometry::MultiPoint(MultiPoint::from(pnts)));
+    }
+
+    if !lns.is_empty() {
+        res.push(Geometry::MultiLineString(merge_lines(node_lines(lns))));
+    }
+
+    if !plys.is_empty() {
+        res.push(Geometry::MultiPolygon(union_polygon_parts(plys)));
+    }
+
+    match res.len() {
+        0 => as_rsgeo_vctr(list!(NULL), "geometry"),
+        1 => {
+            let g = res.pop().unwrap();
+            let cls = match g {
+                Geometry::MultiPoint(_) => "multipoint",
+                Geometry::MultiLineString(_) => "multilinestring",
+                _ => "multipolygon",
+            };
+            as_rsgeo_vctr(list!(Geom::from(g)), cls)
+        }
+        _ => {
+            let gc = GeometryCollection::from(res);
+            let res = Geom::from(Geometry::GeometryCollection(gc));
+            as_rsgeo_vctr(list!(res), "geometrycollection")
+        }
+    }
+}
+
+// splits a geometry into its points, non-degenerate segments, and polygons
+fn collect_parts(x: Geometry, pnts: &mut Vec<Coord>, lns: &mut Vec<Line>, plys: &mut Vec<Polygon>) {
+    // zero length segments are dropped
+    fn push_lines(x: &LineString, lns: &mut Vec<Line>) {
+        lns.extend(x.lines().filter(|l| l.start != l.end));
+    }
+
+    match x {
+        Geometry::Point(p) => pnts.push(p.0),
+        Geometry::MultiPoint(mp) => pnts.extend(mp.iter().map(|p| p.0)),
+        Geometry::Line(l) => push_lines(&LineString::from(l), lns),
+        Geometry::LineString(l) => push_lines(&l, lns),
+        Geometry::MultiLineString(ml) => ml.iter().for_each(|l| push_lines(l, lns)),
+        Geometry::Polygon(p) => plys.push(p),
+        Geometry::MultiPolygon(mp) => plys.extend(mp),
+        Geometry::Rect(r) => plys.push(r.to_polygon()),
+        Geometry::Triangle(t) => plys.push(t.to_polygon()),
+        Geometry::GeometryCollection(gc) => gc
+            .into_iter()
+            .for_each(|g| collect_parts(g, pnts, lns, plys)),
+    }
+}
+
+// unions polygons by recursively splitting them in half and
+// combining the results so that each union is of similar size
+fn union_polygon_parts(mut x: Vec<Polygon>) -> MultiPolygon {
+    if x.len() <= 1 {
+        return MultiPolygon::new(x);
+    }
+
+    let rhs = x.split_off(x.len() / 2);
+    let (lhs, rhs) = rayon::join(|| union_polygon_parts(x), || union_polygon_parts(rhs));
+    lhs.union(&rhs)
+}
+
+// normalizes -0.0 so that coordinates can be used as hash keys
+pub fn coord_key(x: &Coord) -> (u64, u64) {
+    ((x.x + 0.0).to_bits(), (x.y + 0.0).to_bits())
+}
+
+// orders coordinates by x then y. -0.0 is normalized so that equal
+// coordinates are next to each other once sorted.
+fn cmp_coords(a: &Coord, b: &Coord) -> Ordering {
+    (a.x + 0.0)
+        .total_cmp(&(b.x + 0.0))
+        .then((a.y + 0.0).total_cmp(&(b.y + 0.0)))
+}
+
+// The intersections of each pair of segments `(i, j)` where `i < j`. Only
+// segments with intersecting envelopes are tested. Pairs are ordered by `i`.
+// Segments with non-finite coordinates have no envelope and are never paired.
+pub fn segment_intersections(x: &[Line]) -> Vec<(usize, usize, LineIntersection<f64>)> {
+    let items = x
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| {
+            [l.start.x, l.start.y, l.end.x, l.end.y]
+                .iter()
+                .all(|v| v.is_finite())
+        })
+        .map(|(i, l)| GeomWithData::new(RLine::new([l.start.x, l.start.y], [l.end.x, l.end.y]), i))
+        .collect::<Vec<GeomWithData<RLine<[f64; 2]>, usize>>>();
+
+    let tree = RTree::bulk_load(items.clone());
+
+    items
+        .par_iter()
+        .flat_map_iter(|a| {
+            let i = a.data;
+            tree.locate_in_envelope_intersecting(&a.envelope())
+                .filter(move |b| b.data > i)
+                .filter_map(move |b| Some((i, b.data, line_intersection(x[i], x[b.data])?)))
+                .collect::<Vec<(usize, usize, LineIntersection<f64>)>>()
+        })
+        .collect()
+}
+
+// Splits each segment at every point where it intersects another segment.
+// Each intersection is calculated once and added to both segments so that
+// the resulting nodes are identical. The pieces of each segment are returned
+// in order from its start to its end.
+pub fn split_segments(x: &[Line]) -> Vec<Vec<Line>> {
+    let mut splits: Vec<Vec<Coord>> = x.iter().map(|l| vec![l.start, l.end]).collect();
+
+    for (i, j, hit) in segment_intersections(x) {
+        match hit {
+            LineIntersection::SinglePoint { intersection, .. } => {
+                splits[i].push(intersection);
+                splits[j].push(intersection);
+            }
+            LineIntersection::Collinear { intersection } => {
+                splits[i].extend([intersection.start, intersection.end]);
+                splits[j].extend([intersection.start, intersection.end]);
+            }
+        }
+    }
+
+    x.iter()
+        .zip(splits)
+        .map(|(l, mut crds)| {
+            // order the split points by their distance along the segment
+            let pos = |c: &Coord| (c.x - l.start.x) * l.dx() + (c.y - l.start.y) * l.dy();
+            crds.sort_by(|a, b| pos(a).total_cmp(&pos(b)));
+            crds.dedup();
+
+            crds.windows(2)
+                .map(|w| Line::new(w[0], w[1]))
+                .collect::<Vec<Line>>()
+        })
+        .collect()
+}
+
+// orients segments consistently and removes duplicates
+pub fn dedup_segments(mut x: Vec<Line>) -> Vec<Line> {
+    x.iter_mut().for_each(|l| {
+        if cmp_coords(&l.start, &l.end) == Ordering::Greater {
+            *l = Line::new(l.end, l.start);
+        }
+    });
+
+    x.sort_by(|a, b| cmp_coords(&a.start, &b.start).then(cmp_coords(&a.end, &b.end)));
+    x.dedup();
+    x
+}
+
+// nodes segments at their intersections and removes duplicated segments
+pub fn node_lines(x: Vec<Line>) -> Vec<Line> {
+    dedup_segments(split_segments(&x).into_iter().flatten().collect())
+}
+
+// Merges noded segments into linestrings. Linestrings start and end at
+// nodes that are not shared by exactly two segments. Closed rings of
+// segments become closed linestrings.
+pub fn merge_lines(x: Vec<Line>) -> MultiLineString {
+    let mut adj: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+
+    for (i, l) in x.iter().enumerate() {
+        adj.entry(coord_key(&l.start)).or_default().push(i);
+        adj.entry(coord_key(&l.end)).or_default().push(i);
+    }
+
+    let mut used = vec![false; x.len()];
+    let mut res: Vec<LineString> = Vec::new();
+
+    let walk = |start: Coord, from: usize, used: &mut Vec<bool>| {
+        let mut crds = vec![start];
+        let mut cur = start;
+        let mut edge = from;
+
+        loop {
+            used[edge] = true;
+            let l = x[edge];
+            cur = if coord_key(&l.start) == coord_key(&cur) {
+                l.end
+            } else {
+                l.start
+            };
+            crds.push(cur);
+
+            let incident = &adj[&coord_key(&cur)];
+
+            if incident.len() != 2 {
+                break;
+            }
+
+            match incident.iter().find(|&&i| !used[i]) {
+                Some(&i) => edge = i,
+                None => break,
+            }
+        }
+
+        LineString::new(crds)
+    };
+
+    for (i, l) in x.iter().enumerate() {
+        for crd in [l.start, l.end] {
+            if !used[i] && adj[&coord_key(&crd)].len() != 2 {
+                res.push(walk(crd, i, &mut used));
+            }
+        }
+    }
+
+    // any remaining segments form closed rings
+    for i in 0..x.len() {
+        if !used[i] {
+            res.push(walk(x[i].start, i, &mut used));
+        }
+    }
+
+    MultiLineString::new(res)
+}
+
 // fn sort_points(x: &mut Vec<Point>) -> MultiPoint {
 //     x
 //         .sort_by(|a, b| {
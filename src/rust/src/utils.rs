@@ -1,5 +1,13 @@
 use extendr_api::prelude::*;
-use sfconversions::Geom;
+use sfconversions::{
+    geometry_from_list,
+    vctrs::{as_rsgeo_vctr, rsgeo_type, verify_rsgeo},
+    Geom,
+};
+
+use geo_types::{Coord, Geometry, Line, LineString, Point, Polygon};
+use std::mem::size_of;
+
 // Create a blank pointer to be used in ptype casting
 
 #[extendr]
@@ -12,8 +20,153 @@ fn print_geom(x: Robj) -> String {
     Geom::from(x).print().into()
 }
 
+// Creates new geometries that share nothing with `x`. Used to return
+// geometries without the attributes set on the originals.
+#[extendr]
+fn copy_geoms(x: List) -> Robj {
+    verify_rsgeo(&x);
+
+    let cls = rsgeo_type(&x);
+    let res = geometry_from_list(x)
+        .into_iter()
+        .map(|xi| match xi {
+            Some(g) => Geom::from(g).into_robj(),
+            None => NULL.into_robj(),
+        })
+        .collect::<Vec<Robj>>();
+
+    as_rsgeo_vctr(List::from_values(res), cls.as_str())
+}
+
+// The bytes allocated on the heap by a geometry. Vectors are counted by
+// their length rather than capacity so this is a lower bound.
+fn heap_size(x: &Geometry) -> usize {
+    fn linestring(x: &LineString) -> usize {
+        x.0.len() * size_of::<Coord>()
+    }
+
+    fn polygon(x: &Polygon) -> usize {
+        linestring(x.exterior())
+            + x.interiors().iter().map(linestring).sum::<usize>()
+            + x.interiors().len() * size_of::<LineString>()
+    }
+
+    match x {
+        Geometry::LineString(l) => linestring(l),
+        Geometry::Polygon(p) => polygon(p),
+        Geometry::MultiPoint(mp) => mp.0.len() * size_of::<Point>(),
+        Geometry::MultiLineString(ml) => {
+            ml.iter().map(linestring).sum::<usize>() + ml.0.len() * size_of::<LineString>()
+        }
+        Geometry::MultiPolygon(mp) => {
+            mp.iter().map(polygon).sum::<usize>() + mp.0.len() * size_of::<Polygon>()
+        }
+        Geometry::GeometryCollection(gc) => {
+            gc.iter().map(heap_size).sum::<usize>() + gc.0.len() * size_of::<Geometry>()
+        }
+        _ => 0,
+    }
+}
+
+#[extendr]
+/// Geometry Memory Size
+///
+/// Estimates the memory used by each geometry. Geometries are stored outside
+/// of R and referenced by an external pointer so [`object.size()`] only counts
+/// the pointers and not the geometries themselves.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @details
+///
+/// The size is the size of the geometry itself plus the memory allocated for
+/// its coordinates (16 bytes each) and its parts such as rings and
+/// components. Spare capacity and allocator overhead are not counted so the
+/// actual memory used may be somewhat larger.
+///
+/// @export
+/// @returns
+/// `geom_size()` returns a numeric vector of bytes with the same length as
+/// `x`. Missing geometries are `NA`. `geom_size_total()` returns the total
+/// size of all geometries as an `object_size` so it can be formatted with
+/// [`format()`][utils::format.object_size].
+/// @examples
+/// x <- geom_linestring(1:100, runif(100), rep(1:2, each = 50))
+/// geom_size(x)
+/// format(geom_size_total(x), units = "Kb")
+fn geom_size(x: List) -> Doubles {
+    verify_rsgeo(&x);
+
+    x.iter()
+        .map(|(_, xi)| {
+            if xi.is_null() {
+                Rfloat::na()
+            } else {
+                let geom = &<&Geom>::from_robj(&xi).unwrap().geom;
+                Rfloat::from((size_of::<Geom>() + heap_size(geom)) as f64)
+            }
+        })
+        .collect::<Doubles>()
+}
+
+// collects all of the segments that make up a geometry
+pub fn geometry_lines(x: &Geometry) -> Vec<Line> {
+    match x {
+        Geometry::Point(_) | Geometry::MultiPoint(_) => vec![],
+        Geometry::Line(l) => vec![*l],
+        Geometry::LineString(l) => l.lines().collect(),
+        Geometry::MultiLineString(ml) => ml.iter().flat_map(|l| l.lines()).collect(),
+        Geometry::Polygon(p) => std::iter::once(p.exterior())
+            .chain(p.interiors().iter())
+            .flat_map(|r| r.lines())
+            .collect(),
+        Geometry::MultiPolygon(mp) => mp
+            .iter()
+            .flat_map(|p| geometry_lines(&Geometry::Polygon(p.clone())))
+            .collect(),
+        Geometry::Rect(r) => geometry_lines(&Geometry::Polygon(r.to_polygon())),
+        Geometry::Triangle(t) => geometry_lines(&Geometry::Polygon(t.to_polygon())),
+        Geometry::GeometryCollection(gc) => gc.iter().flat_map(geometry_lines).collect(),
+    }
+}
+
+// Creates a data.frame from columns that may include `rsgeo` vectors.
+// `data_frame!()` can't be used for these as `data.frame()` would
+// treat a list column as multiple columns.
+pub fn new_data_frame<const N: usize>(names: [&str; N], values: [Robj; N], n_rows: usize) -> Robj {
+    let row_names = (1..=n_rows as i32).collect::<Vec<i32>>();
+
+    List::from_names_and_values(names, values)
+        .unwrap()
+        .set_class(["data.frame"])
+        .unwrap()
+        .set_attrib("row.names", row_names)
+        .unwrap()
+}
+
+// Runs `f` on a thread pool with `n_threads` threads. Values less than 1
+// use the global pool which has one thread per core unless
+// `RAYON_NUM_THREADS` is set. R objects must not be created inside `f`.
+pub fn with_threads<T, F>(n_threads: i32, f: F) -> T
+where
+    T: Send,
+    F: FnOnce() -> T + Send,
+{
+    if n_threads < 1 {
+        return f();
+    }
+
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(n_threads as usize)
+        .build()
+        .expect("failed to create thread pool")
+        .install(f)
+}
+
 extendr_module! {
     mod utils;
     fn null_pntr;
     fn print_geom;
+    fn copy_geoms;
+    fn geom_size;
 }
@@ -0,0 +1,186 @@
+use extendr_api::prelude::*;
+use sfconversions::{geometry_from_list, vctrs::verify_rsgeo};
+
+use geo::line_intersection::LineIntersection;
+use geo::{Contains, CoordsIter};
+use geo_types::{Coord, Geometry, LineString, Polygon};
+
+use crate::union::segment_intersections;
+use crate::utils::new_data_frame;
+use rayon::prelude::*;
+
+// the reason a geometry is invalid and where the problem is when known
+type Problem = (&'static str, Option<Coord>);
+
+fn non_finite(mut x: impl Iterator<Item = Coord>) -> Option<Problem> {
+    x.find(|c| !(c.x.is_finite() && c.y.is_finite()))
+        .map(|c| ("non-finite coordinate", Some(c)))
+}
+
+fn linestring_problem(x: &LineString) -> Option<Problem> {
+    if let Some(p) = non_finite(x.coords().copied()) {
+        return Some(p);
+    }
+
+    // empty LineStrings are valid
+    match x.0.first() {
+        Some(first) if x.0.iter().all(|c| c == first) => Some(("too few points", Some(*first))),
+        _ => None,
+    }
+}
+
+// Finds where segments of a closed ring cross or touch other than at the
+// vertex shared by consecutive segments. Repeated coordinates are ignored.
+// The intersection of the first pair of segments is reported.
+fn ring_self_intersection(x: &LineString) -> Option<Coord> {
+    let lines = x.lines().filter(|l| l.start != l.end).collect::<Vec<_>>();
+    let n = lines.len();
+
+    segment_intersections(&lines)
+        .into_iter()
+        .filter_map(|(i, j, hit)| {
+            let adjacent = j == i + 1 || (i == 0 && j == n - 1);
+
+            match hit {
+                LineIntersection::SinglePoint { intersection, .. } if !adjacent => {
+                    Some(((i, j), intersection))
+                }
+                LineIntersection::Collinear { intersection } => Some(((i, j), intersection.start)),
+                _ => None,
+            }
+        })
+        .min_by_key(|(pair, _)| *pair)
+        .map(|(_, c)| c)
+}
+
+fn ring_problem(x: &LineString) -> Option<Problem> {
+    if let Some(p) = non_finite(x.coords().copied()) {
+        return Some(p);
+    }
+
+    if x.0.len() < 4 {
+        return Some(("too few points", x.0.first().copied()));
+    }
+
+    if !x.is_closed() {
+        return Some(("unclosed ring", x.0.last().copied()));
+    }
+
+    ring_self_intersection(x).map(|c| ("self-intersection", Some(c)))
+}
+
+fn polygon_problem(x: &Polygon) -> Option<Problem> {
+    if x.exterior().0.is_empty() {
+        return None;
+    }
+
+    for ring in std::iter::once(x.exterior()).chain(x.interiors()) {
+        if let Some(p) = ring_problem(ring) {
+            return Some(p);
+        }
+    }
+
+    let shell = Polygon::new(x.exterior().clone(), vec![]);
+
+    x.interiors()
+        .iter()
+        .find(|hole| !shell.contains(*hole))
+        .map(|hole| ("hole outside shell", hole.0.first().copied()))
+}
+
+pub fn geometry_problem(x: &Geometry) -> Option<Problem> {
+    match x {
+        Geometry::LineString(l) => linestring_problem(l),
+        Geometry::MultiLineString(ml) => ml.iter().find_map(linestring_problem),
+        Geometry::Polygon(p) => polygon_problem(p),
+        Geometry::MultiPolygon(mp) => mp.iter().find_map(polygon_problem),
+        Geometry::GeometryCollection(gc) => gc.iter().find_map(geometry_problem),
+        x => non_finite(x.coords_iter()),
+    }
+}
+
+#[extendr]
+/// Explain Invalid Geometries
+///
+/// Checks whether each geometry is valid and reports the reason and location
+/// of the first problem found. Invalid geometries often cause boolean
+/// operations such as intersections and unions to fail or return unexpected
+/// results. This report makes it easier to find and fix them.
+///
+/// @param x an object of class `rsgeo`
+///
+/// @details
+///
+/// The following problems are reported:
+///
+/// - `"non-finite coordinate"`: a coordinate is infinite or `NaN`.
+/// - `"too few points"`: a LineString has fewer than 2 distinct coordinates
+///   or a polygon ring has fewer than 4 coordinates.
+/// - `"unclosed ring"`: the first and last coordinates of a polygon ring
+///   differ.
+/// - `"self-intersection"`: the segments of a polygon ring cross, touch, or
+///   overlap.
+/// - `"hole outside shell"`: an interior ring is not inside of the exterior
+///   ring.
+///
+/// The components of multi-geometries and geometry collections are checked in
+/// order. Empty geometries are valid. Intersections between different
+/// polygons of a MultiPolygon are not checked.
+///
+/// @export
+/// @returns
+/// A `data.frame` with the same number of rows as `x` and columns:
+///
+/// - `valid`: whether the geometry is valid.
+/// - `reason`: the problem with the geometry. `NA` when it is valid.
+/// - `x`, `y`: the coordinate where the problem was found. `NA` when it is
+///   valid or there is no single location.
+///
+/// Missing geometries result in a row of missing values.
+/// @examples
+/// # a bowtie polygon crosses itself
+/// bowtie <- geom_polygon(c(0, 1, 1, 0, 0), c(0, 1, 0, 1, 0))
+/// validation_report(bowtie)
+fn validation_report(x: List) -> Robj {
+    verify_rsgeo(&x);
+
+    let n = x.len();
+    let res_vec = geometry_from_list(x)
+        .into_par_iter()
+        .map(|xi| xi.map(|g| geometry_problem(&g)))
+        .collect::<Vec<Option<Option<Problem>>>>();
+
+    let mut valid: Vec<Rbool> = Vec::with_capacity(n);
+    let mut reason: Vec<Rstr> = Vec::with_capacity(n);
+    let mut xs: Vec<Option<f64>> = Vec::with_capacity(n);
+    let mut ys: Vec<Option<f64>> = Vec::with_capacity(n);
+
+    for ri in res_vec {
+        let (v, r, c) = match ri {
+            None => (Rbool::na(), Rstr::na(), None),
+            Some(None) => (Rbool::from(true), Rstr::na(), None),
+            Some(Some((r, c))) => (Rbool::from(false), Rstr::from(r), c),
+        };
+
+        valid.push(v);
+        reason.push(r);
+        xs.push(c.map(|c| c.x));
+        ys.push(c.map(|c| c.y));
+    }
+
+    new_data_frame(
+        ["valid", "reason", "x", "y"],
+        [
+            Logicals::from_values(valid).into_robj(),
+            Strings::from_values(reason).into_robj(),
+            Doubles::from_values(xs).into_robj(),
+            Doubles::from_values(ys).into_robj(),
+        ],
+        n,
+    )
+}
+
+extendr_module! {
+    mod validation;
+    fn validation_report;
+}